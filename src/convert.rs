@@ -1,7 +1,12 @@
+// `usize::is_multiple_of` needs Rust 1.87
+#![allow(clippy::manual_is_multiple_of)]
+
 use std::net::Ipv4Addr;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
+use std::collections::HashMap;
 use ascii::AsciiString;
-use crate::option::{NetBiosNodeType, Overload, MessageType, RelayAgentInformationSubOption, Ipv4WithMask, StaticRoute};
+use crate::option::{NetBiosNodeType, Overload, MessageType, RelayAgentInformationSubOption, Ipv4WithMask, StaticRoute, ClientFqdn, ClasslessStaticRoute, SipServers,
+                    ClientMachineIdentifier, Authentication, ClientIdentifier};
 use crate::error::{DhcpError, DhcpResult};
 
 pub const MESSAGE_TYPE_DISCOVER: u8 = 1;
@@ -12,6 +17,23 @@ pub const MESSAGE_TYPE_PACK: u8 = 5;
 pub const MESSAGE_TYPE_NAK: u8 = 6;
 pub const MESSAGE_TYPE_RELEASE: u8 = 7;
 pub const MESSAGE_TYPE_INFORM: u8 = 8;
+pub const MESSAGE_TYPE_FORCERENEW: u8 = 9;
+pub const MESSAGE_TYPE_LEASEQUERY: u8 = 10;
+pub const MESSAGE_TYPE_LEASEUNASSIGNED: u8 = 11;
+pub const MESSAGE_TYPE_LEASEUNKNOWN: u8 = 12;
+pub const MESSAGE_TYPE_LEASEACTIVE: u8 = 13;
+
+// client system architecture types (rfc 4578)
+pub const CLIENT_ARCHITECTURE_INTEL_X86PC: u16 = 0;
+pub const CLIENT_ARCHITECTURE_NEC_PC98: u16 = 1;
+pub const CLIENT_ARCHITECTURE_EFI_ITANIUM: u16 = 2;
+pub const CLIENT_ARCHITECTURE_DEC_ALPHA: u16 = 3;
+pub const CLIENT_ARCHITECTURE_ARC_X86: u16 = 4;
+pub const CLIENT_ARCHITECTURE_INTEL_LEAN_CLIENT: u16 = 5;
+pub const CLIENT_ARCHITECTURE_EFI_IA32: u16 = 6;
+pub const CLIENT_ARCHITECTURE_EFI_BC: u16 = 7;
+pub const CLIENT_ARCHITECTURE_EFI_XSCALE: u16 = 8;
+pub const CLIENT_ARCHITECTURE_EFI_X86_64: u16 = 9;
 
 pub const NODE_TYPE_B: u8 = 1;
 pub const NODE_TYPE_P: u8 = 2;
@@ -20,11 +42,24 @@ pub const NODE_TYPE_H: u8 = 8;
 
 pub const RELAY_AGENT_CIRCUIT: u8 = 1;
 pub const RELAY_AGENT_REMOTE: u8 = 2;
+pub const RELAY_AGENT_LINK_SELECTION: u8 = 5;
+pub const RELAY_AGENT_SUBSCRIBER_ID: u8 = 6;
+
+pub const FQDN_FLAG_S: u8 = 1;
+pub const FQDN_FLAG_O: u8 = 2;
+pub const FQDN_FLAG_E: u8 = 4;
+pub const FQDN_FLAG_N: u8 = 8;
+
+pub const SIP_ENCODING_DOMAIN_NAMES: u8 = 0;
+pub const SIP_ENCODING_ADDRESSES: u8 = 1;
 
 pub const OVERLOAD_FILE: u8 = 1;
 pub const OVERLOAD_SNAME: u8 = 2;
 pub const OVERLOAD_BOTH: u8 = 3;
 
+const DNS_POINTER: u8 = 0xc0;
+const DNS_MAX_LABEL_LENGTH: usize = 63;
+
 macro_rules! impl_length {
     ($t:ty) => {
         impl TryIntoOptionMinBytes<$t> for &[u8] {
@@ -69,13 +104,58 @@ impl_length!(Vec<Ipv4WithMask>);
 
 impl_length!(Vec<StaticRoute>);
 
+impl_length!(ClientFqdn);
+
+impl_length!(Vec<ClasslessStaticRoute>);
+
+impl_length!(Vec<AsciiString>);
+
+impl_length!(Vec<Vec<u8>>);
+
+impl_length!(SipServers);
+
 pub(crate) trait ToOptionBytes {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8>;
+    /// Appends the option data without tag and length to `buf`
+    fn write_option_data(&self, buf: &mut Vec<u8>);
+
+    /// Appends tag, length and data to `buf`
+    ///
+    /// Data exceeding 255 bytes is split into multiple options of the same tag (rfc 3396).
+    fn write_option(&self, tag: u8, buf: &mut Vec<u8>) {
+        let start = buf.len();
+        buf.extend_from_slice(&[tag, 0]);
+        self.write_option_data(buf);
+
+        let length = buf.len() - start - 2;
+        if length <= u8::MAX as usize {
+            buf[start + 1] = length as u8;
+        } else {
+            split_option_in_place(tag, start, length, buf);
+        }
+    }
+
+    /// Number of data bytes [`ToOptionBytes::write_option_data`] appends
+    fn option_data_len(&self) -> usize;
+
+    /// Number of bytes [`ToOptionBytes::write_option`] appends, including all tag and length bytes
+    fn option_len(&self) -> usize {
+        let length = self.option_data_len();
+        length + 2 * length.div_ceil(u8::MAX as usize).max(1)
+    }
+
+    #[cfg(test)]
+    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write_option(tag, &mut bytes);
+        bytes
+    }
 }
 
 impl TryToOption<Ipv4Addr> for &[u8] {
     fn try_from_option(&self, tag: u8) -> DhcpResult<Ipv4Addr> {
-        let fixed: [u8; 4] = self[0..4].try_into().map_err(|_| DhcpError::OptionParseError(tag))?;
+        let fixed: [u8; 4] = self.get(0..4)
+            .and_then(|b| b.try_into().ok())
+            .ok_or(DhcpError::OptionParseError(tag))?;
         Ok(Ipv4Addr::from(fixed))
     }
 }
@@ -96,25 +176,25 @@ impl TryToOption<Vec<Ipv4Addr>> for &[u8] {
 
 impl TryToOption<u8> for &[u8] {
     fn try_from_option(&self, tag: u8) -> DhcpResult<u8> {
-        self[0..1].try_into().map(u8::from_be_bytes).map_err(|_| DhcpError::OptionParseError(tag))
+        self.first().copied().ok_or(DhcpError::OptionParseError(tag))
     }
 }
 
 impl TryToOption<u16> for &[u8] {
     fn try_from_option(&self, tag: u8) -> DhcpResult<u16> {
-        self[0..2].try_into().map(u16::from_be_bytes).map_err(|_| DhcpError::OptionParseError(tag))
+        self.get(0..2).and_then(|b| b.try_into().ok()).map(u16::from_be_bytes).ok_or(DhcpError::OptionParseError(tag))
     }
 }
 
 impl TryToOption<u32> for &[u8] {
     fn try_from_option(&self, tag: u8) -> DhcpResult<u32> {
-        self[0..4].try_into().map(u32::from_be_bytes).map_err(|_| DhcpError::OptionParseError(tag))
+        self.get(0..4).and_then(|b| b.try_into().ok()).map(u32::from_be_bytes).ok_or(DhcpError::OptionParseError(tag))
     }
 }
 
 impl TryToOption<i32> for &[u8] {
     fn try_from_option(&self, tag: u8) -> DhcpResult<i32> {
-        self[0..4].try_into().map(i32::from_be_bytes).map_err(|_| DhcpError::OptionParseError(tag))
+        self.get(0..4).and_then(|b| b.try_into().ok()).map(i32::from_be_bytes).ok_or(DhcpError::OptionParseError(tag))
     }
 }
 
@@ -126,7 +206,7 @@ impl TryToOption<AsciiString> for &[u8] {
 
 impl TryToOption<bool> for &[u8] {
     fn try_from_option(&self, tag: u8) -> DhcpResult<bool> {
-        self.get(0)
+        self.first()
             .ok_or(DhcpError::OptionParseError(tag))
             .map(|value| {
                 match value {
@@ -160,19 +240,20 @@ impl TryToOption<Vec<u16>> for &[u8] {
 
 impl TryToOption<NetBiosNodeType> for &[u8] {
     fn try_from_option(&self, tag: u8) -> DhcpResult<NetBiosNodeType> {
-        match self.get(0).ok_or(DhcpError::OptionParseError(tag)) {
+        match self.first().ok_or(DhcpError::OptionParseError(tag)) {
             Ok(&NODE_TYPE_B) => Ok(NetBiosNodeType::B),
             Ok(&NODE_TYPE_P) => Ok(NetBiosNodeType::P),
             Ok(&NODE_TYPE_M) => Ok(NetBiosNodeType::M),
             Ok(&NODE_TYPE_H) => Ok(NetBiosNodeType::H),
-            _ => Err(DhcpError::OptionParseError(tag))
+            Ok(other) => Ok(NetBiosNodeType::Other(*other)),
+            Err(e) => Err(e)
         }
     }
 }
 
 impl TryToOption<Overload> for &[u8] {
     fn try_from_option(&self, tag: u8) -> DhcpResult<Overload> {
-        match self.get(0).ok_or(DhcpError::OptionParseError(tag)) {
+        match self.first().ok_or(DhcpError::OptionParseError(tag)) {
             Ok(&OVERLOAD_FILE) => Ok(Overload::File),
             Ok(&OVERLOAD_SNAME) => Ok(Overload::Sname),
             Ok(&OVERLOAD_BOTH) => Ok(Overload::Both),
@@ -183,7 +264,7 @@ impl TryToOption<Overload> for &[u8] {
 
 impl TryToOption<MessageType> for &[u8] {
     fn try_from_option(&self, tag: u8) -> DhcpResult<MessageType> {
-        match self.get(0).ok_or(DhcpError::OptionParseError(tag)) {
+        match self.first().ok_or(DhcpError::OptionParseError(tag)) {
             Ok(&MESSAGE_TYPE_DISCOVER) => Ok(MessageType::Discover),
             Ok(&MESSAGE_TYPE_OFFER) => Ok(MessageType::Offer),
             Ok(&MESSAGE_TYPE_REQUEST) => Ok(MessageType::Request),
@@ -192,6 +273,11 @@ impl TryToOption<MessageType> for &[u8] {
             Ok(&MESSAGE_TYPE_NAK) => Ok(MessageType::Nak),
             Ok(&MESSAGE_TYPE_RELEASE) => Ok(MessageType::Release),
             Ok(&MESSAGE_TYPE_INFORM) => Ok(MessageType::Inform),
+            Ok(&MESSAGE_TYPE_FORCERENEW) => Ok(MessageType::ForceRenew),
+            Ok(&MESSAGE_TYPE_LEASEQUERY) => Ok(MessageType::LeaseQuery),
+            Ok(&MESSAGE_TYPE_LEASEUNASSIGNED) => Ok(MessageType::LeaseUnassigned),
+            Ok(&MESSAGE_TYPE_LEASEUNKNOWN) => Ok(MessageType::LeaseUnknown),
+            Ok(&MESSAGE_TYPE_LEASEACTIVE) => Ok(MessageType::LeaseActive),
             _ => Err(DhcpError::OptionParseError(tag))
         }
     }
@@ -202,14 +288,21 @@ impl TryToOption<Vec<RelayAgentInformationSubOption>> for &[u8] {
         let mut result = vec![];
         let mut bytes = *self;
         loop {
-            let sub_tag = bytes.get(0).ok_or(DhcpError::OptionParseError(tag))?;
+            let sub_tag = bytes.first().ok_or(DhcpError::OptionParseError(tag))?;
             let length = *bytes.get(1).ok_or(DhcpError::OptionParseError(tag))? as usize + 2;
-            let data = bytes[2..length].to_vec();
+            let data = bytes.get(2..length).ok_or(DhcpError::OptionParseError(tag))?.to_vec();
 
             result.push(match *sub_tag {
                 RELAY_AGENT_CIRCUIT => RelayAgentInformationSubOption::AgentCircuit(data),
                 RELAY_AGENT_REMOTE => RelayAgentInformationSubOption::AgentRemote(data),
-                _ => RelayAgentInformationSubOption::Unknown(data),
+                RELAY_AGENT_LINK_SELECTION => match <[u8; 4]>::try_from(data.as_slice()) {
+                    Ok(ip) => RelayAgentInformationSubOption::LinkSelection(Ipv4Addr::from(ip)),
+                    Err(_) => return Err(DhcpError::OptionParseError(tag)),
+                },
+                RELAY_AGENT_SUBSCRIBER_ID => RelayAgentInformationSubOption::SubscriberId(
+                    AsciiString::from_ascii(data).map_err(|_| DhcpError::OptionParseError(tag))?
+                ),
+                _ => RelayAgentInformationSubOption::Unknown(*sub_tag, data),
             });
 
             bytes = &bytes[length..];
@@ -253,144 +346,332 @@ impl TryToOption<Vec<StaticRoute>> for &[u8] {
     }
 }
 
+impl TryToOption<Vec<ClasslessStaticRoute>> for &[u8] {
+    fn try_from_option(&self, tag: u8) -> DhcpResult<Vec<ClasslessStaticRoute>> {
+        let mut result = vec![];
+        let mut bytes = *self;
+        while let Some(prefix_length) = bytes.first() {
+            if *prefix_length > 32 {
+                return Err(DhcpError::OptionParseError(tag));
+            }
+
+            // 1 prefix length + significant octets + 4 router
+            let significant = (*prefix_length as usize).div_ceil(8);
+            let length = 1 + significant + 4;
+            let route = bytes.get(..length).ok_or(DhcpError::OptionParseError(tag))?;
+
+            let mut destination = [0; 4];
+            destination[..significant].copy_from_slice(&route[1..1 + significant]);
+
+            result.push(ClasslessStaticRoute {
+                destination: Ipv4Addr::from(destination),
+                prefix_length: *prefix_length,
+                router: (&route[1 + significant..]).try_from_option(tag)?,
+            });
+
+            bytes = &bytes[length..];
+        }
+
+        Ok(result)
+    }
+}
+
+impl TryToOption<ClientFqdn> for &[u8] {
+    fn try_from_option(&self, tag: u8) -> DhcpResult<ClientFqdn> {
+        if self.len() < 3 {
+            return Err(DhcpError::OptionParseError(tag));
+        }
+
+        Ok(ClientFqdn {
+            flags: self[0],
+            rcode1: self[1],
+            rcode2: self[2],
+            name: self[3..].to_vec(),
+        })
+    }
+}
+
+/// Decodes a single rfc 1035 name starting at `position`, returns the name and the position after it
+///
+/// Compression pointers must point before every position visited so far, which rules out loops.
+fn read_dns_name(data: &[u8], mut position: usize, tag: u8) -> DhcpResult<(AsciiString, usize)> {
+    let mut name = vec![];
+    let mut limit = position;
+    let mut next = None;
+
+    loop {
+        let length = *data.get(position).ok_or(DhcpError::OptionParseError(tag))?;
+
+        if length == 0 {
+            position += 1;
+            break;
+        } else if length & DNS_POINTER == DNS_POINTER {
+            let low = *data.get(position + 1).ok_or(DhcpError::OptionParseError(tag))?;
+            let target = ((length & !DNS_POINTER) as usize) << 8 | low as usize;
+            if target >= limit {
+                return Err(DhcpError::OptionParseError(tag));
+            }
+
+            next.get_or_insert(position + 2);
+            limit = target;
+            position = target;
+        } else if length & DNS_POINTER == 0 {
+            let label = data.get(position + 1..position + 1 + length as usize)
+                .ok_or(DhcpError::OptionParseError(tag))?;
+            if !name.is_empty() {
+                name.push(b'.');
+            }
+            name.extend_from_slice(label);
+            position += 1 + length as usize;
+        } else {
+            return Err(DhcpError::OptionParseError(tag));
+        }
+    }
+
+    let name = AsciiString::from_ascii(name).map_err(|_| DhcpError::OptionParseError(tag))?;
+    Ok((name, next.unwrap_or(position)))
+}
+
+impl TryToOption<Vec<AsciiString>> for &[u8] {
+    fn try_from_option(&self, tag: u8) -> DhcpResult<Vec<AsciiString>> {
+        let mut result = vec![];
+        let mut position = 0;
+
+        while position < self.len() {
+            let (name, next) = read_dns_name(self, position, tag)?;
+            result.push(name);
+            position = next;
+        }
+
+        Ok(result)
+    }
+}
+
+impl TryToOption<Vec<Vec<u8>>> for &[u8] {
+    fn try_from_option(&self, tag: u8) -> DhcpResult<Vec<Vec<u8>>> {
+        let mut result = vec![];
+        let mut bytes = *self;
+
+        while let Some((length, rest)) = bytes.split_first() {
+            let instance = rest.get(..*length as usize).ok_or(DhcpError::OptionParseError(tag))?;
+            result.push(instance.to_vec());
+            bytes = &rest[*length as usize..];
+        }
+
+        Ok(result)
+    }
+}
+
+impl TryToOption<SipServers> for &[u8] {
+    fn try_from_option(&self, tag: u8) -> DhcpResult<SipServers> {
+        // compression pointers are relative to the first name, behind the encoding byte
+        match self.split_first() {
+            Some((&SIP_ENCODING_DOMAIN_NAMES, names)) => names.try_from_option(tag).map(SipServers::DomainNames),
+            Some((&SIP_ENCODING_ADDRESSES, addresses)) => addresses.try_from_option(tag).map(SipServers::Addresses),
+            _ => Err(DhcpError::OptionParseError(tag))
+        }
+    }
+}
+
+impl TryToOption<ClientMachineIdentifier> for &[u8] {
+    fn try_from_option(&self, tag: u8) -> DhcpResult<ClientMachineIdentifier> {
+        match self.split_first() {
+            Some((typ, uuid)) => Ok(ClientMachineIdentifier {
+                typ: *typ,
+                uuid: uuid.try_into().map_err(|_| DhcpError::OptionParseError(tag))?,
+            }),
+            None => Err(DhcpError::OptionParseError(tag))
+        }
+    }
+}
+
+impl TryToOption<Authentication> for &[u8] {
+    fn try_from_option(&self, tag: u8) -> DhcpResult<Authentication> {
+        if self.len() < 11 {
+            return Err(DhcpError::OptionParseError(tag));
+        }
+
+        Ok(Authentication {
+            protocol: self[0],
+            algorithm: self[1],
+            rdm: self[2],
+            replay_detection: u64::from_be_bytes(self[3..11].try_into().unwrap()),
+            auth_info: self[11..].to_vec(),
+        })
+    }
+}
+
+impl ToOptionBytes for () {
+    fn write_option_data(&self, _buf: &mut Vec<u8>) {}
+
+    fn option_data_len(&self) -> usize {
+        0
+    }
+}
+
 impl ToOptionBytes for Ipv4Addr {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data = self.octets().to_vec();
-        data.insert(0, data.len() as u8);
-        data.insert(0, tag);
-        data
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.octets());
+    }
+
+    fn option_data_len(&self) -> usize {
+        4
     }
 }
 
 impl ToOptionBytes for Vec<Ipv4Addr> {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut bytes = vec![tag];
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
         self.iter().for_each(|ip| {
-            bytes.extend_from_slice(&ip.octets());
+            buf.extend_from_slice(&ip.octets());
         });
-        bytes.insert(1, (bytes.len() - 1) as u8);
-        bytes
+    }
+
+    fn option_data_len(&self) -> usize {
+        4 * self.len()
     }
 }
 
 impl ToOptionBytes for u16 {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data = vec![tag, 2];
-        data.extend(&self.to_be_bytes());
-        data
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_be_bytes());
+    }
+
+    fn option_data_len(&self) -> usize {
+        2
     }
 }
 
 impl ToOptionBytes for u32 {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data = vec![tag, 4];
-        data.extend(&self.to_be_bytes());
-        data
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_be_bytes());
+    }
+
+    fn option_data_len(&self) -> usize {
+        4
     }
 }
 
 impl ToOptionBytes for i16 {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data = vec![tag, 2];
-        data.extend(&self.to_be_bytes());
-        data
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_be_bytes());
+    }
+
+    fn option_data_len(&self) -> usize {
+        2
     }
 }
 
 impl ToOptionBytes for i32 {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data = vec![tag, 4];
-        data.extend(&self.to_be_bytes());
-        data
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_be_bytes());
+    }
+
+    fn option_data_len(&self) -> usize {
+        4
     }
 }
 
 impl ToOptionBytes for AsciiString {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data: Vec<u8> = self.as_bytes().to_vec();
-        data.insert(0, data.len() as u8);
-        data.insert(0, tag);
-        data
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn option_data_len(&self) -> usize {
+        self.len()
     }
 }
 
 impl ToOptionBytes for &bool {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        vec![tag, 1, match self {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.push(match self {
             false => 0,
             true => 1
-        }]
+        });
+    }
+
+    fn option_data_len(&self) -> usize {
+        1
     }
 }
 
 impl ToOptionBytes for &Vec<(Ipv4Addr, Ipv4Addr)> {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data = vec![];
-
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
         self.iter().for_each(|ips| {
-            data.extend_from_slice(&ips.0.octets());
-            data.extend_from_slice(&ips.1.octets());
+            buf.extend_from_slice(&ips.0.octets());
+            buf.extend_from_slice(&ips.1.octets());
         });
-        data.insert(0, data.len() as u8);
-        data.insert(0, tag);
+    }
 
-        data
+    fn option_data_len(&self) -> usize {
+        8 * self.len()
     }
 }
 
 impl ToOptionBytes for &u8 {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        vec![tag, 1, **self]
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.push(**self);
+    }
+
+    fn option_data_len(&self) -> usize {
+        1
     }
 }
 
 impl ToOptionBytes for &Vec<u16> {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data: Vec<u8> = vec![];
-        self.iter().map(|b| b.to_be_bytes()).for_each(|s| {
-            data.push(s[0]);
-            data.push(s[1]);
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        self.iter().for_each(|b| {
+            buf.extend_from_slice(&b.to_be_bytes());
         });
+    }
 
-        data.insert(0, data.len() as u8);
-        data.insert(0, tag);
-        data
+    fn option_data_len(&self) -> usize {
+        2 * self.len()
     }
 }
 
 impl ToOptionBytes for &Vec<u8> {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data = self.to_vec();
-        data.insert(0, data.len() as u8);
-        data.insert(0, tag);
-        data
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self);
+    }
+
+    fn option_data_len(&self) -> usize {
+        self.len()
     }
 }
 
 impl ToOptionBytes for &NetBiosNodeType {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        vec![tag, 1, match self {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.push(match self {
             NetBiosNodeType::B => NODE_TYPE_B,
             NetBiosNodeType::P => NODE_TYPE_P,
             NetBiosNodeType::M => NODE_TYPE_M,
-            NetBiosNodeType::H => NODE_TYPE_H
-        }]
+            NetBiosNodeType::H => NODE_TYPE_H,
+            NetBiosNodeType::Other(value) => *value,
+        });
+    }
+
+    fn option_data_len(&self) -> usize {
+        1
     }
 }
 
 impl ToOptionBytes for &Overload {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        vec![tag, 1, match self {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.push(match self {
             Overload::File => OVERLOAD_FILE,
             Overload::Sname => OVERLOAD_SNAME,
             Overload::Both => OVERLOAD_BOTH,
-        }]
+        });
+    }
+
+    fn option_data_len(&self) -> usize {
+        1
     }
 }
 
 impl ToOptionBytes for &MessageType {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        vec![tag, 1, match self {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.push(match self {
             MessageType::Discover => MESSAGE_TYPE_DISCOVER,
             MessageType::Offer => MESSAGE_TYPE_OFFER,
             MessageType::Request => MESSAGE_TYPE_REQUEST,
@@ -399,59 +680,258 @@ impl ToOptionBytes for &MessageType {
             MessageType::Nak => MESSAGE_TYPE_NAK,
             MessageType::Release => MESSAGE_TYPE_RELEASE,
             MessageType::Inform => MESSAGE_TYPE_INFORM,
-        }]
+            MessageType::ForceRenew => MESSAGE_TYPE_FORCERENEW,
+            MessageType::LeaseQuery => MESSAGE_TYPE_LEASEQUERY,
+            MessageType::LeaseUnassigned => MESSAGE_TYPE_LEASEUNASSIGNED,
+            MessageType::LeaseUnknown => MESSAGE_TYPE_LEASEUNKNOWN,
+            MessageType::LeaseActive => MESSAGE_TYPE_LEASEACTIVE,
+        });
+    }
+
+    fn option_data_len(&self) -> usize {
+        1
     }
 }
 
 impl ToOptionBytes for &Vec<RelayAgentInformationSubOption> {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut sub_options: Vec<u8> = self.iter().map(|r| {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        self.iter().for_each(|r| {
             let (sub_tag, data) = match r {
-                RelayAgentInformationSubOption::AgentRemote(sub_data) => (RELAY_AGENT_REMOTE, sub_data),
-                RelayAgentInformationSubOption::AgentCircuit(sub_data) => (RELAY_AGENT_CIRCUIT, sub_data),
-                RelayAgentInformationSubOption::Unknown(sub_data) => (0, sub_data),
+                RelayAgentInformationSubOption::AgentRemote(sub_data) => (RELAY_AGENT_REMOTE, sub_data.to_vec()),
+                RelayAgentInformationSubOption::AgentCircuit(sub_data) => (RELAY_AGENT_CIRCUIT, sub_data.to_vec()),
+                RelayAgentInformationSubOption::LinkSelection(ip) => (RELAY_AGENT_LINK_SELECTION, ip.octets().to_vec()),
+                RelayAgentInformationSubOption::SubscriberId(id) => (RELAY_AGENT_SUBSCRIBER_ID, id.as_bytes().to_vec()),
+                RelayAgentInformationSubOption::Unknown(sub_tag, sub_data) => (*sub_tag, sub_data.to_vec()),
             };
 
-            let mut data: Vec<u8> = data.iter().copied().collect();
-            data.insert(0, data.len() as u8);
-            data.insert(0, sub_tag);
-            data
-        }).flatten().collect();
-
-        sub_options.insert(0, sub_options.len() as u8);
-        sub_options.insert(0, tag);
+            buf.push(sub_tag);
+            buf.push(data.len() as u8);
+            buf.extend_from_slice(&data);
+        });
+    }
 
-        sub_options
+    fn option_data_len(&self) -> usize {
+        self.iter().map(|r| 2 + match r {
+            RelayAgentInformationSubOption::AgentRemote(sub_data) => sub_data.len(),
+            RelayAgentInformationSubOption::AgentCircuit(sub_data) => sub_data.len(),
+            RelayAgentInformationSubOption::LinkSelection(_) => 4,
+            RelayAgentInformationSubOption::SubscriberId(id) => id.len(),
+            RelayAgentInformationSubOption::Unknown(_, sub_data) => sub_data.len(),
+        }).sum()
     }
 }
 
 impl ToOptionBytes for &Vec<Ipv4WithMask> {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data = vec![];
-
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
         self.iter().for_each(|ips| {
-            data.extend_from_slice(&ips.ipv4addr.octets());
-            data.extend_from_slice(&ips.mask.octets());
+            buf.extend_from_slice(&ips.ipv4addr.octets());
+            buf.extend_from_slice(&ips.mask.octets());
         });
-        data.insert(0, data.len() as u8);
-        data.insert(0, tag);
+    }
 
-        data
+    fn option_data_len(&self) -> usize {
+        8 * self.len()
     }
 }
 
 impl ToOptionBytes for &Vec<StaticRoute> {
-    fn to_option_bytes(&self, tag: u8) -> Vec<u8> {
-        let mut data = vec![];
-
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
         self.iter().for_each(|ips| {
-            data.extend_from_slice(&ips.destination.octets());
-            data.extend_from_slice(&ips.router.octets());
+            buf.extend_from_slice(&ips.destination.octets());
+            buf.extend_from_slice(&ips.router.octets());
         });
-        data.insert(0, data.len() as u8);
-        data.insert(0, tag);
+    }
 
-        data
+    fn option_data_len(&self) -> usize {
+        8 * self.len()
+    }
+}
+
+impl ToOptionBytes for &Vec<ClasslessStaticRoute> {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        self.iter().for_each(|route| {
+            let significant = (route.prefix_length as usize).div_ceil(8);
+            buf.push(route.prefix_length);
+            buf.extend_from_slice(&route.destination.octets()[..significant]);
+            buf.extend_from_slice(&route.router.octets());
+        });
+    }
+
+    fn option_data_len(&self) -> usize {
+        self.iter().map(|route| 1 + (route.prefix_length as usize).div_ceil(8) + 4).sum()
+    }
+}
+
+/// Appends names with rfc 1035 compression to `buf`, pointers are relative to the first name
+pub(crate) fn write_dns_names(names: &[AsciiString], buf: &mut Vec<u8>) {
+    encode_dns_names(names, Some(buf));
+}
+
+/// Number of bytes [`write_dns_names`] appends
+fn dns_names_len(names: &[AsciiString]) -> usize {
+    encode_dns_names(names, None)
+}
+
+/// Fails if a label of `names` is empty or longer than 63 bytes (rfc 1035 2.3.4)
+pub(crate) fn check_dns_names(names: &[AsciiString], tag: u8) -> DhcpResult<()> {
+    if names.iter().all(is_valid_dns_name) {
+        Ok(())
+    } else {
+        Err(DhcpError::OptionInvalidValueError(tag))
+    }
+}
+
+fn is_valid_dns_name(name: &AsciiString) -> bool {
+    let name = name.as_str();
+    let name = name.strip_suffix('.').unwrap_or(name);
+    name.is_empty() || name.split('.').all(|label| !label.is_empty() && label.len() <= DNS_MAX_LABEL_LENGTH)
+}
+
+/// Compresses `names`, writing to `buf` if given, and returns the encoded length
+///
+/// Names failing [`check_dns_names`] cannot be encoded and are left out.
+fn encode_dns_names(names: &[AsciiString], mut buf: Option<&mut Vec<u8>>) -> usize {
+    // suffix -> offset of its first occurrence
+    let mut suffixes: HashMap<&str, usize> = HashMap::new();
+    let mut length = 0;
+    let mut append = |bytes: &[u8]| {
+        if let Some(buf) = buf.as_mut() {
+            buf.extend_from_slice(bytes);
+        }
+        bytes.len()
+    };
+
+    for name in names.iter().filter(|name| is_valid_dns_name(name)) {
+        let name = name.as_str();
+        let mut rest = name.strip_suffix('.').unwrap_or(name);
+
+        loop {
+            if rest.is_empty() {
+                length += append(&[0]);
+                break;
+            }
+            if let Some(offset) = suffixes.get(rest) {
+                length += append(&(0xc000 | *offset as u16).to_be_bytes());
+                break;
+            }
+            if length < 0x4000 {
+                suffixes.insert(rest, length);
+            }
+
+            let (label, tail) = rest.split_once('.').unwrap_or((rest, ""));
+            length += append(&[label.len() as u8]) + append(label.as_bytes());
+            rest = tail;
+        }
+    }
+
+    length
+}
+
+/// Turns `length` bytes of data at `start + 2` into consecutive options of the same tag holding
+/// at most 255 bytes each (rfc 3396), the data is moved in place to make room for the headers
+fn split_option_in_place(tag: u8, start: usize, length: usize, buf: &mut Vec<u8>) {
+    let max = u8::MAX as usize;
+    let chunks = length.div_ceil(max);
+    buf.resize(start + length + 2 * chunks, 0);
+
+    // back to front, so no chunk is overwritten before it has been moved
+    for i in (0..chunks).rev() {
+        let from = start + 2 + i * max;
+        let size = max.min(length - i * max);
+        let to = start + 2 + i * (max + 2);
+        buf.copy_within(from..from + size, to);
+        buf[to - 2] = tag;
+        buf[to - 1] = size as u8;
+    }
+}
+
+impl ToOptionBytes for &Vec<AsciiString> {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        write_dns_names(self, buf);
+    }
+
+    fn option_data_len(&self) -> usize {
+        dns_names_len(self)
+    }
+}
+
+impl ToOptionBytes for &Vec<Vec<u8>> {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        self.iter().for_each(|instance| {
+            buf.push(instance.len() as u8);
+            buf.extend_from_slice(instance);
+        });
+    }
+
+    fn option_data_len(&self) -> usize {
+        self.iter().map(|instance| 1 + instance.len()).sum()
+    }
+}
+
+impl ToOptionBytes for &SipServers {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        match self {
+            SipServers::DomainNames(names) => {
+                buf.push(SIP_ENCODING_DOMAIN_NAMES);
+                write_dns_names(names, buf);
+            },
+            SipServers::Addresses(addresses) => {
+                buf.push(SIP_ENCODING_ADDRESSES);
+                addresses.iter().for_each(|ip| buf.extend_from_slice(&ip.octets()));
+            },
+        }
+    }
+
+    fn option_data_len(&self) -> usize {
+        1 + match self {
+            SipServers::DomainNames(names) => dns_names_len(names),
+            SipServers::Addresses(addresses) => 4 * addresses.len(),
+        }
+    }
+}
+
+impl ToOptionBytes for &ClientIdentifier {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.push(self.typ);
+        buf.extend_from_slice(&self.data);
+    }
+
+    fn option_data_len(&self) -> usize {
+        1 + self.data.len()
+    }
+}
+
+impl ToOptionBytes for &ClientMachineIdentifier {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.push(self.typ);
+        buf.extend_from_slice(&self.uuid);
+    }
+
+    fn option_data_len(&self) -> usize {
+        1 + self.uuid.len()
+    }
+}
+
+impl ToOptionBytes for &Authentication {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&[self.protocol, self.algorithm, self.rdm]);
+        buf.extend_from_slice(&self.replay_detection.to_be_bytes());
+        buf.extend_from_slice(&self.auth_info);
+    }
+
+    fn option_data_len(&self) -> usize {
+        11 + self.auth_info.len()
+    }
+}
+
+impl ToOptionBytes for &ClientFqdn {
+    fn write_option_data(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&[self.flags, self.rcode1, self.rcode2]);
+        buf.extend_from_slice(&self.name);
+    }
+
+    fn option_data_len(&self) -> usize {
+        3 + self.name.len()
     }
 }
 
@@ -475,33 +955,33 @@ fn test_parse_ipv4_vec() {
 fn test_parse_u8() {
     let bytes: &[u8] = &[111];
     let result: u8 = bytes.try_from_option(0).unwrap();
-    assert_eq!(111 as u8, result);
+    assert_eq!(111_u8, result);
 }
 
 #[test]
 fn test_parse_u16() {
     let bytes: &[u8] = &[111, 222];
     let result: u16 = bytes.try_from_option(0).unwrap();
-    assert_eq!(28638 as u16, result);
+    assert_eq!(28638_u16, result);
 }
 
 #[test]
 fn test_parse_u32() {
     let bytes: &[u8] = &[111, 222, 111, 222];
     let result: u32 = bytes.try_from_option(0).unwrap();
-    assert_eq!(1876848606 as u32, result);
+    assert_eq!(1876848606_u32, result);
 }
 
 #[test]
 fn test_parse_i32() {
     let bytes: &[u8] = &[1, 2, 3, 4];
     let result: i32 = bytes.try_from_option(0).unwrap();
-    assert_eq!(16909060 as i32, result);
+    assert_eq!(16909060_i32, result);
 }
 
 #[test]
 fn test_parse_ascii_string() {
-    let bytes: &[u8] = &['D' as u8, 'h' as u8, 'c' as u8, 'p' as u8];
+    let bytes: &[u8] = b"Dhcp";
     let result: AsciiString = bytes.try_from_option(0).unwrap();
     assert_eq!(AsciiString::from(vec![ascii::AsciiChar::D,
                                       ascii::AsciiChar::h,
@@ -514,7 +994,7 @@ fn test_parse_ascii_string() {
 fn test_parse_bool() {
     let bytes: &[u8] = &[0];
     let result: bool = bytes.try_from_option(0).unwrap();
-    assert_eq!(false, result);
+    assert!(!result);
 }
 
 #[test]
@@ -586,96 +1066,96 @@ fn test_parse_static_route_vec() {
 
 #[test]
 fn test_into_bytes_ipv4() {
-    let bytes: &[u8] = &[0 as u8, 4, 1, 2, 3, 4];
+    let bytes: &[u8] = &[0_u8, 4, 1, 2, 3, 4];
     assert_eq!(bytes, Ipv4Addr::new(1, 2, 3, 4).to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_ipv4_vec() {
-    let bytes: &[u8] = &[0 as u8, 8, 1, 2, 3, 4, 5, 5, 5, 5];
+    let bytes: &[u8] = &[0_u8, 8, 1, 2, 3, 4, 5, 5, 5, 5];
     assert_eq!(bytes, vec![Ipv4Addr::new(1, 2, 3, 4), Ipv4Addr::new(5, 5, 5, 5)]
         .to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_u16() {
-    let bytes: &[u8] = &[0 as u8, 2, 1, 0];
-    assert_eq!(bytes, (256 as u16).to_option_bytes(0));
+    let bytes: &[u8] = &[0_u8, 2, 1, 0];
+    assert_eq!(bytes, 256_u16.to_option_bytes(0));
 }
 
 #[test]
 fn test_into_bytes_u32() {
-    let bytes: &[u8] = &[0 as u8, 4, 255, 255, 255, 255];
+    let bytes: &[u8] = &[0_u8, 4, 255, 255, 255, 255];
     assert_eq!(bytes, u32::MAX.to_option_bytes(0));
 }
 
 #[test]
 fn test_into_bytes_i16() {
-    let bytes: &[u8] = &[0 as u8, 2, 127, 255];
+    let bytes: &[u8] = &[0_u8, 2, 127, 255];
     assert_eq!(bytes, i16::MAX.to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_i32() {
-    let bytes: &[u8] = &[0 as u8, 4, 127, 255, 255, 255];
+    let bytes: &[u8] = &[0_u8, 4, 127, 255, 255, 255];
     assert_eq!(bytes, i32::MAX.to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_ascii_string() {
-    let bytes: &[u8] = &[0 as u8, 2, 65, 122];
+    let bytes: &[u8] = &[0_u8, 2, 65, 122];
     assert_eq!(bytes, AsciiString::from(&[ascii::AsciiChar::A, ascii::AsciiChar::z] as &[ascii::AsciiChar]).to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_bool() {
-    let bytes: &[u8] = &[0 as u8, 1, 0];
+    let bytes: &[u8] = &[0_u8, 1, 0];
     assert_eq!(bytes, (&false).to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_u8() {
-    let bytes: &[u8] = &[0 as u8, 1, 5];
-    assert_eq!(bytes, (&(5 as u8)).to_option_bytes(0));
+    let bytes: &[u8] = &[0_u8, 1, 5];
+    assert_eq!(bytes, (&5_u8).to_option_bytes(0));
 }
 
 #[test]
 fn test_into_bytes_u16_vec() {
-    let bytes: &[u8] = &[0 as u8, 2, 4, 87];
-    assert_eq!(bytes, (&(1111 as u16)).to_option_bytes(0));
+    let bytes: &[u8] = &[0_u8, 2, 4, 87];
+    assert_eq!(bytes, 1111_u16.to_option_bytes(0));
 }
 
 #[test]
 fn test_into_bytes_u8_vec() {
-    let bytes: &[u8] = &[0 as u8, 3, 0, 2, 3];
-    assert_eq!(bytes, (&vec![0 as u8, 2, 3]).to_option_bytes(0))
+    let bytes: &[u8] = &[0_u8, 3, 0, 2, 3];
+    assert_eq!(bytes, (&vec![0_u8, 2, 3]).to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_netbios_node_type() {
-    let bytes: &[u8] = &[0 as u8, 1, NODE_TYPE_M];
+    let bytes: &[u8] = &[0_u8, 1, NODE_TYPE_M];
     assert_eq!(bytes, (&NODE_TYPE_M).to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_overload() {
-    let bytes: &[u8] = &[0 as u8, 1, OVERLOAD_SNAME];
+    let bytes: &[u8] = &[0_u8, 1, OVERLOAD_SNAME];
     assert_eq!(bytes, (&OVERLOAD_SNAME).to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_message_type() {
-    let bytes: &[u8] = &[0 as u8, 1, MESSAGE_TYPE_DECLINE];
+    let bytes: &[u8] = &[0_u8, 1, MESSAGE_TYPE_DECLINE];
     assert_eq!(bytes, (&MESSAGE_TYPE_DECLINE).to_option_bytes(0))
 }
 
 #[test]
 fn test_into_bytes_relay_agent_information_vec() {
-    let bytes: &[u8] = &[0 as u8,
+    let bytes: &[u8] = &[0_u8,
         8,
         1, 1, 1,
         2, 3, 5, 6, 7];
-    let data = vec![RelayAgentInformationSubOption::AgentCircuit(vec![1 as u8]),
+    let data = vec![RelayAgentInformationSubOption::AgentCircuit(vec![1_u8]),
                     RelayAgentInformationSubOption::AgentRemote(vec![5, 6, 7])];
 
     assert_eq!(bytes, (&data).to_option_bytes(0))
@@ -683,9 +1163,132 @@ fn test_into_bytes_relay_agent_information_vec() {
 
 #[test]
 fn test_into_bytes_ipv4mask() {
-    let bytes: &[u8] = &[0 as u8, 8, 1, 1, 2, 2, 3, 3, 4, 4];
+    let bytes: &[u8] = &[0_u8, 8, 1, 1, 2, 2, 3, 3, 4, 4];
     assert_eq!(bytes, (&vec![Ipv4WithMask {
         ipv4addr: Ipv4Addr::new(1, 1, 2, 2),
         mask: Ipv4Addr::new(3, 3, 4, 4),
     }]) .to_option_bytes(0));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_domain_search_compression() {
+    let names = vec![
+        AsciiString::from_ascii("example.com").unwrap(),
+        AsciiString::from_ascii("sub.example.com").unwrap(),
+    ];
+
+    let bytes = (&names).to_option_bytes(119);
+    assert_eq!(bytes, vec![119, 19,
+                           7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0,
+                           3, b's', b'u', b'b', 0xc0, 0]);
+
+    let result: Vec<AsciiString> = (&bytes[2..]).try_from_option(119).unwrap();
+    assert_eq!(result, names);
+}
+
+#[test]
+fn test_domain_search_invalid_labels() {
+    let long = AsciiString::from_ascii(format!("{}.com", "a".repeat(64))).unwrap();
+    let max = AsciiString::from_ascii(format!("{}.com", "a".repeat(63))).unwrap();
+    let valid = AsciiString::from_ascii("example.com.").unwrap();
+
+    for invalid in ["a..b", ".com", "example.com.."] {
+        let names = vec![valid.clone(), AsciiString::from_ascii(invalid).unwrap()];
+        assert!(matches!(check_dns_names(&names, 119), Err(DhcpError::OptionInvalidValueError(119))));
+        assert_eq!((&names).to_option_bytes(119), (&vec![valid.clone()]).to_option_bytes(119));
+    }
+
+    let names = vec![long, valid.clone()];
+    assert!(matches!(check_dns_names(&names, 119), Err(DhcpError::OptionInvalidValueError(119))));
+    assert_eq!((&names).to_option_bytes(119), (&vec![valid.clone()]).to_option_bytes(119));
+    assert!(check_dns_names(&[max, valid], 119).is_ok());
+}
+
+#[test]
+fn test_domain_search_invalid_pointer() {
+    let forward: &[u8] = &[0xc0, 2, 3, b'c', b'o', b'm', 0];
+    assert!(matches!(TryToOption::<Vec<AsciiString>>::try_from_option(&forward, 119),
+                     Err(DhcpError::OptionParseError(119))));
+
+    let out_of_range: &[u8] = &[3, b'c', b'o', b'm', 0, 0xc0, 40];
+    assert!(matches!(TryToOption::<Vec<AsciiString>>::try_from_option(&out_of_range, 119),
+                     Err(DhcpError::OptionParseError(119))));
+}
+
+#[test]
+fn test_relay_link_selection() {
+    let data = vec![RelayAgentInformationSubOption::LinkSelection(Ipv4Addr::new(10, 0, 1, 0))];
+    let bytes = (&data).to_option_bytes(82);
+    assert_eq!(bytes, vec![82, 6, RELAY_AGENT_LINK_SELECTION, 4, 10, 0, 1, 0]);
+
+    let result: Vec<RelayAgentInformationSubOption> = (&bytes[2..]).try_from_option(82).unwrap();
+    assert_eq!(result, data);
+
+    let wrong_length: &[u8] = &[RELAY_AGENT_LINK_SELECTION, 3, 10, 0, 1];
+    assert!(matches!(TryToOption::<Vec<RelayAgentInformationSubOption>>::try_from_option(&wrong_length, 82),
+                     Err(DhcpError::OptionParseError(82))));
+}
+
+#[test]
+fn test_parse_netbios_node_type_other() {
+    let bytes: &[u8] = &[NODE_TYPE_H];
+    let result: NetBiosNodeType = bytes.try_from_option(46).unwrap();
+    assert_eq!(result, NetBiosNodeType::H);
+
+    let bytes: &[u8] = &[16];
+    let result: NetBiosNodeType = bytes.try_from_option(46).unwrap();
+    assert_eq!(result, NetBiosNodeType::Other(16));
+    assert_eq!((&result).to_option_bytes(46), vec![46, 1, 16]);
+}
+
+#[test]
+fn test_parse_relay_agent_truncated() {
+    let bytes: &[u8] = &[RELAY_AGENT_REMOTE, 3, 1, 2];
+    assert!(matches!(TryToOption::<Vec<RelayAgentInformationSubOption>>::try_from_option(&bytes, 82),
+                     Err(DhcpError::OptionParseError(82))));
+}
+
+#[test]
+fn test_relay_agent_unknown_sub_option() {
+    let bytes: &[u8] = &[9, 2, 1, 2, RELAY_AGENT_CIRCUIT, 1, 3];
+    let result: Vec<RelayAgentInformationSubOption> = bytes.try_from_option(82).unwrap();
+    assert_eq!(result, vec![RelayAgentInformationSubOption::Unknown(9, vec![1, 2]),
+                            RelayAgentInformationSubOption::AgentCircuit(vec![3])]);
+
+    assert_eq!(&(&result).to_option_bytes(82)[2..], bytes);
+}
+
+#[test]
+fn test_relay_agent_mixed_sub_options() {
+    let data = vec![
+        RelayAgentInformationSubOption::AgentCircuit(vec![1, 2]),
+        RelayAgentInformationSubOption::AgentRemote(vec![3]),
+        RelayAgentInformationSubOption::LinkSelection(Ipv4Addr::new(192, 168, 10, 0)),
+    ];
+    let bytes = (&data).to_option_bytes(82);
+    assert_eq!(bytes, vec![82, 13,
+                           RELAY_AGENT_CIRCUIT, 2, 1, 2,
+                           RELAY_AGENT_REMOTE, 1, 3,
+                           RELAY_AGENT_LINK_SELECTION, 4, 192, 168, 10, 0]);
+
+    let result: Vec<RelayAgentInformationSubOption> = (&bytes[2..]).try_from_option(82).unwrap();
+    assert_eq!(result, data);
+
+    let too_long: &[u8] = &[RELAY_AGENT_CIRCUIT, 1, 1, RELAY_AGENT_LINK_SELECTION, 5, 192, 168, 10, 0, 0];
+    assert!(matches!(TryToOption::<Vec<RelayAgentInformationSubOption>>::try_from_option(&too_long, 82),
+                     Err(DhcpError::OptionParseError(82))));
+}
+
+#[test]
+fn test_relay_agent_subscriber_id() {
+    let data = vec![RelayAgentInformationSubOption::SubscriberId(AsciiString::from_ascii("sub-42").unwrap())];
+    let bytes = (&data).to_option_bytes(82);
+    assert_eq!(bytes, vec![82, 8, RELAY_AGENT_SUBSCRIBER_ID, 6, b's', b'u', b'b', b'-', b'4', b'2']);
+
+    let result: Vec<RelayAgentInformationSubOption> = (&bytes[2..]).try_from_option(82).unwrap();
+    assert_eq!(result, data);
+
+    let invalid: &[u8] = &[RELAY_AGENT_SUBSCRIBER_ID, 2, b'a', 0xff];
+    assert!(matches!(TryToOption::<Vec<RelayAgentInformationSubOption>>::try_from_option(&invalid, 82),
+                     Err(DhcpError::OptionParseError(82))));
+}
@@ -4,17 +4,29 @@ use std::convert::{TryInto, TryFrom};
 use std::fmt::Debug;
 use std::ops::{Deref, Range, RangeFrom};
 use crate::error::{DhcpError, DhcpResult};
-use crate::option::{DhcpOptions, DhcpOption,
+use crate::option::{DhcpOptions, DhcpOption, RawOptions, ParseOptions,
                     PARAMETER_REQUEST_LIST,
                     IP_ADDRESS_LEASE_TIME,
                     REQUESTED_IP_ADDRESS,
                     MESSAGE_TYPE,
                     SERVER_IDENTIFIER,
-                    MESSAGE
+                    MESSAGE,
+                    CLIENT_IDENTIFIER,
+                    MAXIMUM_DHCP_MESSAGE_SIZE,
+                    TFTP_SERVER_NAME,
+                    END,
+                    TFTP_SERVER_ADDRESS,
+                    RELAY_AGENT_INFORMATION,
+                    OPTION_OVERLOAD,
+                    PAD,
+                    Overload,
+                    RelayAgentInformationSubOption,
+                    ClientIdentifier,
 };
 
 #[cfg(feature = "with_serde")]
 use serde::{Serialize, Deserialize, Deserializer, Serializer};
+use crate::option::MessageType;
 use ascii::{AsciiString, AsciiChar};
 use std::collections::HashMap;
 
@@ -27,9 +39,15 @@ pub const MESSAGE_OPERATION_BOOT_REQUEST: u8 = 1;
 pub const MESSAGE_OPERATION_BOOT_REPLY: u8 = 2;
 
 pub const HARDWARE_ADDRESS_TYPE_ETHERNET: u8 = 1;
+pub const HARDWARE_ADDRESS_TYPE_IEEE802: u8 = 6;
+pub const HARDWARE_ADDRESS_TYPE_FDDI: u8 = 8;
+
+/// Relays discard packets above this hop count (rfc1542)
+pub const DEFAULT_MAX_HOPS: u8 = 16;
 
 const OP: usize = 0;
 const HARDWARE_TYPE: usize = 1;
+const HARDWARE_LENGTH: usize = 2;
 const HOPS: usize = 3;
 const XID: Range<usize> = 4..8;
 const SECONDS: Range<usize> = 8..10;
@@ -40,9 +58,11 @@ const SERVER_IP: Range<usize> = 20..24;
 const GATEWAY_IP: Range<usize> = 24..28;
 const CLIENT_HARDWARE_6: Range<usize> = 28..34;
 const CLIENT_HARDWARE_8: Range<usize> = 28..36;
+const CLIENT_HARDWARE: Range<usize> = 28..44;
 const SERVER_HOSTNAME: Range<usize> = 44..108;
 const FILENAME: Range<usize> = 108..236;
 const COOKIE: Range<usize> = 236..240;
+const VENDOR: RangeFrom<usize> = 236..;
 const OPTIONS: RangeFrom<usize> = 240..;
 
 fn ipv4_from_bytes(data: &[u8], error: DhcpError) -> DhcpResult<Ipv4Addr> {
@@ -50,6 +70,71 @@ fn ipv4_from_bytes(data: &[u8], error: DhcpError) -> DhcpResult<Ipv4Addr> {
     Ok(Ipv4Addr::from(fixed))
 }
 
+/// Fast check whether a datagram plausibly is a DHCP packet
+///
+/// Only length and magic cookie are checked, neither the header nor the options are parsed.
+pub fn is_dhcp_packet(bytes: &[u8]) -> bool {
+    bytes.get(COOKIE) == Some(DHCP_COOKIE)
+}
+
+/// DHCP server port
+pub const SERVER_PORT: u16 = 67;
+
+/// DHCP client port
+pub const CLIENT_PORT: u16 = 68;
+
+const UDP_HEADER_LENGTH: usize = 8;
+#[cfg(feature = "ethernet")]
+const ETHERNET_HEADER_LENGTH: usize = 14;
+#[cfg(feature = "ethernet")]
+const ETHER_TYPE_IPV4: &[u8] = &[0x08, 0x00];
+#[cfg(feature = "ethernet")]
+const IPV4_MIN_HEADER_LENGTH: usize = 20;
+#[cfg(feature = "ethernet")]
+const IP_PROTOCOL_UDP: u8 = 17;
+
+/// Parses a UDP datagram, header included, carrying a DHCP packet
+///
+/// The datagram is cut to the UDP length field, checksums are not verified. Fails with
+/// [`DhcpError::NotDhcpPort`] if the destination port is neither 67 nor 68.
+pub fn from_udp_datagram(datagram: &[u8]) -> DhcpResult<DhcpPacket> {
+    let header = datagram.get(..UDP_HEADER_LENGTH).ok_or(DhcpError::FrameParseError("truncated udp header"))?;
+    let destination = u16::from_be_bytes([header[2], header[3]]);
+    if destination != SERVER_PORT && destination != CLIENT_PORT {
+        return Err(DhcpError::NotDhcpPort);
+    }
+
+    let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+    let payload = datagram.get(UDP_HEADER_LENGTH..length).ok_or(DhcpError::FrameParseError("invalid udp length"))?;
+    DhcpPacket::try_from(payload)
+}
+
+/// Parses an untagged Ethernet II frame carrying IPv4, UDP and a DHCP packet, e.g. from a pcap capture
+///
+/// The IPv4 header length is taken from the IHL field and trailing frame padding is ignored.
+#[cfg(feature = "ethernet")]
+pub fn from_ethernet_frame(frame: &[u8]) -> DhcpResult<DhcpPacket> {
+    if frame.get(12..ETHERNET_HEADER_LENGTH) != Some(ETHER_TYPE_IPV4) {
+        return Err(DhcpError::FrameParseError("not an ipv4 frame"));
+    }
+
+    let ip = &frame[ETHERNET_HEADER_LENGTH..];
+    let header_length = match ip.first() {
+        Some(version_ihl) if version_ihl >> 4 == 4 => (version_ihl & 0x0f) as usize * 4,
+        _ => return Err(DhcpError::FrameParseError("not an ipv4 frame")),
+    };
+    if header_length < IPV4_MIN_HEADER_LENGTH || ip.len() < header_length {
+        return Err(DhcpError::FrameParseError("truncated ipv4 header"));
+    }
+    if ip[9] != IP_PROTOCOL_UDP {
+        return Err(DhcpError::FrameParseError("not a udp packet"));
+    }
+
+    let total_length = u16::from_be_bytes([ip[2], ip[3]]) as usize;
+    let datagram = ip.get(header_length..total_length).ok_or(DhcpError::FrameParseError("invalid ipv4 total length"))?;
+    from_udp_datagram(datagram)
+}
+
 fn byte_to_char(byte: &u8) -> Option<AsciiChar> {
     if byte != &0 {
         AsciiChar::from_ascii(*byte).ok()
@@ -68,10 +153,17 @@ fn bytes_fill_zeroes(bytes: &[u8], length: u8) -> Vec<u8> {
     filled
 }
 
+/// Appends `bytes` zero padded to `length`
+fn write_fill_zeroes(buf: &mut Vec<u8>, bytes: &[u8], length: usize) {
+    let end = buf.len() + length;
+    buf.extend_from_slice(bytes);
+    buf.resize(end, 0);
+}
+
 /// Wrapper over [`MacAddr`] to support serde
 ///
 /// `<https://github.com/svartalf/rust-macaddr/pull/3>`
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub struct MacAddress {
     #[cfg_attr(feature = "with_serde",
@@ -107,6 +199,13 @@ impl MacAddress {
     }
 }
 
+impl MacAddress {
+    /// All-zero hardware address as sent by some relays and clients
+    pub fn is_zero(&self) -> bool {
+        self.mac.as_bytes().iter().all(|b| *b == 0)
+    }
+}
+
 impl From<MacAddr> for MacAddress {
     fn from(mac: MacAddr) -> Self {
         Self {
@@ -152,30 +251,49 @@ impl MacAddrSize for MacAddr {
     }
 }
 
-
+/// Identifies the client a lease belongs to
+///
+/// See [`DhcpPacket::lease_key`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
+pub enum LeaseKey {
+    HardwareAddress(MacAddress),
+    ClientIdentifier(ClientIdentifier),
+}
 
 /// Hardware Address type
-#[derive(Debug, PartialEq)]
+///
+/// `Other` keeps any type without a variant, e.g. `0` sent by some identifier-only clients. Only
+/// `Other` accepts a hardware address length of 0, the client hardware address is all-zero then.
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub enum HardwareAddressType {
-    Ethernet
+    Ethernet,
+    IeeeIbm802,
+    Fddi,
+    Other(u8),
 }
 
 impl TryFrom<&u8> for HardwareAddressType {
     type Error = DhcpError;
 
     fn try_from(value: &u8) -> Result<Self, Self::Error> {
-        match *value {
-            HARDWARE_ADDRESS_TYPE_ETHERNET => Ok(HardwareAddressType::Ethernet),
-            _ => Err(DhcpError::HardwareAddressTypeParseError)
-        }
+        Ok(match *value {
+            HARDWARE_ADDRESS_TYPE_ETHERNET => HardwareAddressType::Ethernet,
+            HARDWARE_ADDRESS_TYPE_IEEE802 => HardwareAddressType::IeeeIbm802,
+            HARDWARE_ADDRESS_TYPE_FDDI => HardwareAddressType::Fddi,
+            other => HardwareAddressType::Other(other),
+        })
     }
 }
 
 impl From<HardwareAddressType> for u8 {
     fn from(t: HardwareAddressType) -> Self {
         match t {
-            HardwareAddressType::Ethernet => 1,
+            HardwareAddressType::Ethernet => HARDWARE_ADDRESS_TYPE_ETHERNET,
+            HardwareAddressType::IeeeIbm802 => HARDWARE_ADDRESS_TYPE_IEEE802,
+            HardwareAddressType::Fddi => HARDWARE_ADDRESS_TYPE_FDDI,
+            HardwareAddressType::Other(t) => t,
         }
     }
 }
@@ -185,7 +303,7 @@ impl From<HardwareAddressType> for u8 {
 /// Describes `op` field in dhcp packet.
 ///
 /// Client uses [`MessageOperation::BootRequest`] and Server uses [`MessageOperation::BootReply`]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub enum MessageOperation {
     BootRequest,
@@ -216,20 +334,25 @@ impl TryFrom<&u8> for MessageOperation {
 /// Transmission behaviour during dhcp communication.
 ///
 /// Client uses broadcast until network configuration is done.
-#[derive(Debug, PartialEq)]
+///
+/// Only the broadcast bit, the most significant bit of the flags field, is evaluated.
+/// The other 15 bits are reserved (rfc2131 2) and ignored when parsing.
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub enum Flags {
     Unicast,
     Broadcast,
 }
 
+const FLAG_BROADCAST: u8 = 0x80;
+
 impl TryFrom<&[u8]> for Flags {
     type Error = DhcpError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
         match *value {
-            [0, 0] => Ok(Self::Unicast),
-            [0, 1] => Ok(Self::Broadcast),
+            [high, _] if high & FLAG_BROADCAST != 0 => Ok(Self::Broadcast),
+            [_, _] => Ok(Self::Unicast),
             _ => Err(DhcpError::InvalidFlag)
         }
     }
@@ -239,17 +362,33 @@ impl From<Flags> for &[u8] {
     fn from(f: Flags) -> Self {
         match f {
             Flags::Unicast => &[0, 0],
-            Flags::Broadcast => &[0, 1],
+            Flags::Broadcast => &[FLAG_BROADCAST, 0],
         }
     }
 }
 
 
 /// Dhcp uses always `Dhcp` cookie.
+///
+/// `None` marks a BOOTP packet without cookie, the vendor area is kept as raw bytes then.
+/// `Other` keeps a non-standard vendor cookie, the vendor area is kept as raw bytes as well.
 #[derive(Debug, PartialEq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub enum Cookie {
-    Dhcp
+    Dhcp,
+    None,
+    Other([u8; 4]),
+}
+
+impl Cookie {
+    /// Cookie as it appears on the wire, empty for [`Cookie::None`]
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Cookie::Dhcp => DHCP_COOKIE,
+            Cookie::None => &[],
+            Cookie::Other(cookie) => cookie,
+        }
+    }
 }
 
 impl TryFrom<&[u8]> for Cookie {
@@ -258,23 +397,37 @@ impl TryFrom<&[u8]> for Cookie {
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
         match value {
             DHCP_COOKIE => Ok(Self::Dhcp),
-            _ => Err(DhcpError::CookieParseError)
+            [0, 0, 0, 0] => Ok(Self::None),
+            _ => value.try_into().map(Self::Other).map_err(|_| DhcpError::CookieParseError)
         }
     }
 }
 
+/// Same bytes as [`Cookie::as_bytes`]
+///
+/// An owned [`Cookie::Other`] has no static bytes to borrow from, so they are leaked. Prefer
+/// [`Cookie::as_bytes`] or the conversion from `&Cookie` for non-standard cookies.
 impl From<Cookie> for &[u8] {
     fn from(c: Cookie) -> Self {
         match c {
-            Cookie::Dhcp => DHCP_COOKIE
+            Cookie::Dhcp => DHCP_COOKIE,
+            Cookie::None => &[],
+            Cookie::Other(cookie) => Box::leak(Box::new(cookie)),
         }
     }
 }
+
+impl<'a> From<&'a Cookie> for &'a [u8] {
+    fn from(c: &'a Cookie) -> Self {
+        c.as_bytes()
+    }
+}
+
 /// Represents a complete DHCP packet.
 ///
 /// Use `try_from` to parse from UDP packet or `into` to serialize into bytes.
 ///
-/// Construct a new packet from scratch with [`DhcpPacket::new`]
+/// Construct a new packet from scratch with [`DhcpPacket::builder`] or [`DhcpPacket::new`]
 #[derive(Debug)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub struct DhcpPacket {
@@ -293,9 +446,34 @@ pub struct DhcpPacket {
     pub(crate) filename: AsciiString,
     pub(crate) cookie: Cookie,
     pub(crate) options: DhcpOptions,
+    #[cfg_attr(feature = "with_serde", serde(skip))]
+    pub(crate) raw_options: Option<RawOptions>,
+    pub(crate) vendor: Vec<u8>,
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
+/// Compares all packet fields, the raw options kept for forwarding are ignored
+impl PartialEq for DhcpPacket {
+    fn eq(&self, other: &Self) -> bool {
+        self.operation == other.operation
+            && self.hardware_type == other.hardware_type
+            && self.hops == other.hops
+            && self.transaction_id == other.transaction_id
+            && self.seconds == other.seconds
+            && self.flags == other.flags
+            && self.client == other.client
+            && self.your == other.your
+            && self.server == other.server
+            && self.gateway == other.gateway
+            && self.client_hardware == other.client_hardware
+            && self.server_hostname == other.server_hostname
+            && self.filename == other.filename
+            && self.cookie == other.cookie
+            && self.options == other.options
+            && self.vendor == other.vendor
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 impl DhcpPacket {
     pub fn new<I, C, S, O>(
         operation: MessageOperation,
@@ -334,6 +512,61 @@ impl DhcpPacket {
             filename: filename.into(),
             cookie: Cookie::Dhcp,
             options: options.into(),
+            raw_options: None,
+            vendor: vec![],
+        }
+    }
+
+    /// Creates the REQUEST of a client in INIT-REBOOT state (rfc2131 4.3.2)
+    ///
+    /// The previously assigned address is requested with option 50, `ciaddr` stays zero and no
+    /// server identifier is set since no server was selected.
+    pub fn init_reboot_request<C>(client_mac: C,
+                                  requested_ip: Ipv4Addr,
+                                  transaction_id: u32,
+                                  parameter_request_list: Option<Vec<u8>>,
+    ) -> Self
+        where
+            C: Into<MacAddress>,
+    {
+        let mut options = DhcpOptions::from(vec![
+            DhcpOption::MessageType(MessageType::Request),
+            DhcpOption::RequestedIpAddress(requested_ip),
+            DhcpOption::End,
+        ]);
+        options.upsert_option(parameter_request_list.map(DhcpOption::ParameterRequestList));
+
+        Self::new(
+            MessageOperation::BootRequest,
+            HardwareAddressType::Ethernet,
+            0,
+            transaction_id,
+            0,
+            Flags::Unicast,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            client_mac,
+            AsciiString::new(),
+            AsciiString::new(),
+            options,
+        )
+    }
+
+    /// Number of bytes the packet serializes to
+    ///
+    /// Includes the `End` option which is appended automatically if missing.
+    pub fn serialized_len(&self) -> usize {
+        match self.cookie {
+            Cookie::Dhcp => {
+                let options: usize = self.options.options().iter()
+                    .filter(|o| !matches!(o, DhcpOption::End))
+                    .map(|o| o.encoded_len())
+                    .sum();
+                OPTIONS.start + options + 1
+            }
+            _ => VENDOR.start + self.vendor.len(),
         }
     }
 
@@ -345,17 +578,24 @@ impl DhcpPacket {
     pub fn hardware_type(&self) -> &HardwareAddressType {
         &self.hardware_type
     }
-    pub fn hops(&self) -> &MessageOperation {
-        &self.operation
+    pub fn hops(&self) -> u8 {
+        self.hops
     }
-    pub fn transaction_id(&self) -> &MessageOperation {
-        &self.operation
+    pub fn transaction_id(&self) -> u32 {
+        self.transaction_id
     }
-    pub fn seconds(&self) -> &MessageOperation {
-        &self.operation
+    pub fn seconds(&self) -> u16 {
+        self.seconds
     }
-    pub fn flags(&self) -> &MessageOperation {
-        &self.operation
+    pub fn flags(&self) -> &Flags {
+        &self.flags
+    }
+    /// Whether the client asks for broadcast replies
+    pub fn is_broadcast(&self) -> bool {
+        self.flags == Flags::Broadcast
+    }
+    pub fn is_unicast(&self) -> bool {
+        self.flags == Flags::Unicast
     }
     pub fn client(&self) -> &Ipv4Addr {
         &self.client
@@ -370,7 +610,15 @@ impl DhcpPacket {
         &self.gateway
     }
     pub fn client_hardware(&self) -> &MacAddr {
-        &*self.client_hardware
+        &self.client_hardware
+    }
+    /// Value of the `hlen` field, 0 for an all-zero address of [`HardwareAddressType::Other`]
+    fn hardware_length(&self) -> u8 {
+        if matches!(self.hardware_type, HardwareAddressType::Other(_)) && self.client_hardware.is_zero() {
+            0
+        } else {
+            self.client_hardware.size()
+        }
     }
     pub fn hostname(&self) -> &str {
         self.server_hostname.as_str()
@@ -382,16 +630,59 @@ impl DhcpPacket {
         &self.cookie
     }
 
+    pub fn set_transaction_id(&mut self, transaction_id: u32) {
+        self.transaction_id = transaction_id;
+    }
+    pub fn set_flags(&mut self, flags: Flags) {
+        self.flags = flags;
+    }
+    pub fn set_client(&mut self, client: Ipv4Addr) {
+        self.client = client;
+    }
+    pub fn set_your(&mut self, your: Ipv4Addr) {
+        self.your = your;
+    }
+    pub fn set_server(&mut self, server: Ipv4Addr) {
+        self.server = server;
+    }
+    /// Set the relay agent address (giaddr)
+    pub fn set_gateway(&mut self, gateway: Ipv4Addr) {
+        self.gateway = gateway;
+    }
+
+    /// Raw vendor area starting at the cookie position of a BOOTP packet
+    ///
+    /// Returns `None` for DHCP packets, use the options instead.
+    pub fn bootp_vendor_data(&self) -> Option<&[u8]> {
+        match self.cookie {
+            Cookie::Dhcp => None,
+            _ => Some(&self.vendor)
+        }
+    }
+
     pub fn option(&self, tag: u8) -> Option<&DhcpOption> {
         self.options.option(tag)
     }
     pub fn options(&self) -> &DhcpOptions {
         &self.options
     }
+    /// Mutable access to the options
+    ///
+    /// Discards the raw options kept from parsing, see [`DhcpPacket::raw_options`].
     pub fn options_mut(&mut self) -> &mut DhcpOptions {
+        self.raw_options = None;
         &mut self.options
     }
 
+    /// Options as `(tag, data)` pairs in order
+    ///
+    /// If the packet was parsed from bytes and the options were not modified since, the payloads
+    /// are returned exactly as received. Useful for proxies forwarding options byte-for-byte.
+    /// Otherwise the typed options are encoded.
+    pub fn raw_options(&self) -> RawOptions {
+        self.raw_options.clone().unwrap_or_else(|| self.options.to_raw())
+    }
+
     /* dhcp control options */
 
     pub fn client_requested_ip(&self) -> Option<&DhcpOption> {
@@ -403,9 +694,53 @@ impl DhcpPacket {
     pub fn message_type(&self) -> Option<&DhcpOption> {
         self.option(MESSAGE_TYPE)
     }
+    /// Value of the message type option (53)
+    pub fn message_type_value(&self) -> Option<MessageType> {
+        self.options.message_type_value()
+    }
     pub fn server_identifier(&self) -> Option<&DhcpOption> {
         self.option(SERVER_IDENTIFIER)
     }
+    /// Address of the server identifier option (54)
+    ///
+    /// This identifies the DHCP server. Not to be confused with [`DhcpPacket::next_server`].
+    pub fn server_identifier_ip(&self) -> Option<Ipv4Addr> {
+        self.options.server_identifier()
+    }
+    /// Address of the next server to use in bootstrap (`siaddr` field)
+    ///
+    /// This is not necessarily the DHCP server, use [`DhcpPacket::server_identifier_ip`] for that.
+    pub fn next_server(&self) -> Ipv4Addr {
+        self.server
+    }
+    /// Subnet selected by the relay agent (option 82, sub-option 5)
+    pub fn relay_link_selection(&self) -> Option<Ipv4Addr> {
+        match self.option(RELAY_AGENT_INFORMATION) {
+            Some(DhcpOption::RelayAgentInformation(sub_options)) => sub_options.iter().find_map(|s| match s {
+                RelayAgentInformationSubOption::LinkSelection(ip) => Some(*ip),
+                _ => None
+            }),
+            _ => None
+        }
+    }
+    /// TFTP servers to boot from in order of precedence
+    ///
+    /// Option 66 if present, else the addresses of option 150, else the `siaddr` field if set.
+    pub fn tftp_servers(&self) -> Vec<String> {
+        if let Some(DhcpOption::TftpServer(name)) = self.option(TFTP_SERVER_NAME) {
+            return vec![name.to_string()];
+        }
+
+        if let Some(DhcpOption::TftpServerAddress(addresses)) = self.option(TFTP_SERVER_ADDRESS) {
+            return addresses.iter().map(Ipv4Addr::to_string).collect();
+        }
+
+        if self.server.is_unspecified() {
+            vec![]
+        } else {
+            vec![self.server.to_string()]
+        }
+    }
     pub fn client_parameter_request_list(&self) -> Option<&DhcpOption> {
         self.option(PARAMETER_REQUEST_LIST)
     }
@@ -413,6 +748,129 @@ impl DhcpPacket {
         self.option(MESSAGE)
     }
 
+    /// Maximum DHCP message size the client accepts
+    ///
+    /// Read it from the request before converting it into a reply, replies must not carry option 57.
+    pub fn client_max_message_size(&self) -> Option<u16> {
+        match self.option(MAXIMUM_DHCP_MESSAGE_SIZE) {
+            Some(DhcpOption::MaximumDhcpMessageSize(size)) => Some(*size),
+            _ => None
+        }
+    }
+
+    /// Checks the packet is consistent with rfc2131
+    ///
+    /// Requires a message type matching the `op` direction, a hardware address length matching
+    /// the hardware type and a sane combination of server identifier, requested ip and client
+    /// address in a REQUEST (rfc2131 4.3.2). Returns the first violation.
+    pub fn validate(&self) -> DhcpResult<()> {
+        let message_type = self.message_type_value()
+            .ok_or(DhcpError::PacketValidation("missing message type option"))?;
+
+        let operation = match message_type {
+            MessageType::Offer | MessageType::Ack | MessageType::Nak | MessageType::ForceRenew
+            | MessageType::LeaseUnassigned | MessageType::LeaseUnknown | MessageType::LeaseActive => MessageOperation::BootReply,
+            _ => MessageOperation::BootRequest,
+        };
+        if self.operation != operation {
+            return Err(DhcpError::PacketValidation("operation does not match message type"));
+        }
+
+        let hardware_size = match self.hardware_type {
+            HardwareAddressType::Ethernet | HardwareAddressType::IeeeIbm802 | HardwareAddressType::Fddi => Some(MAC_V6_SIZE),
+            HardwareAddressType::Other(_) => None,
+        };
+        if matches!(hardware_size, Some(size) if self.client_hardware.size() != size) {
+            return Err(DhcpError::PacketValidation("hardware address length does not match hardware type"));
+        }
+
+        if message_type == MessageType::Request {
+            let requested_ip = self.option(REQUESTED_IP_ADDRESS).is_some();
+            if self.option(SERVER_IDENTIFIER).is_some() && !requested_ip {
+                return Err(DhcpError::PacketValidation("request with server identifier requires a requested ip"));
+            }
+            if requested_ip && !self.client.is_unspecified() {
+                return Err(DhcpError::PacketValidation("request with client address must not contain a requested ip"));
+            }
+            if !requested_ip && self.client.is_unspecified() {
+                return Err(DhcpError::PacketValidation("request requires a requested ip or client address"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Key to store the lease of this client
+    ///
+    /// Uses the client hardware address. Falls back to the client identifier if the
+    /// hardware address is all zeros, otherwise different clients would collide.
+    pub fn lease_key(&self) -> Option<LeaseKey> {
+        if !self.client_hardware.is_zero() {
+            return Some(LeaseKey::HardwareAddress(self.client_hardware.clone()));
+        }
+
+        match self.option(CLIENT_IDENTIFIER) {
+            Some(DhcpOption::ClientIdentifier(id)) => Some(LeaseKey::ClientIdentifier(id.clone())),
+            _ => None
+        }
+    }
+
+    /// Human readable dump of the header fields followed by one present option per line
+    pub fn pretty_print(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        let _ = writeln!(out, "op: {:?}", self.operation);
+        let _ = writeln!(out, "htype: {:?}", self.hardware_type);
+        let _ = writeln!(out, "hops: {}", self.hops);
+        let _ = writeln!(out, "xid: {:#010x}", self.transaction_id);
+        let _ = writeln!(out, "secs: {}", self.seconds);
+        let _ = writeln!(out, "flags: {:?}", self.flags);
+        let _ = writeln!(out, "ciaddr: {}", self.client);
+        let _ = writeln!(out, "yiaddr: {}", self.your);
+        let _ = writeln!(out, "siaddr: {}", self.server);
+        let _ = writeln!(out, "giaddr: {}", self.gateway);
+        let _ = writeln!(out, "chaddr: {}", *self.client_hardware);
+        if !self.server_hostname.is_empty() {
+            let _ = writeln!(out, "sname: {}", self.server_hostname);
+        }
+        if !self.filename.is_empty() {
+            let _ = writeln!(out, "file: {}", self.filename);
+        }
+        let _ = writeln!(out, "options:");
+        for (_, option) in self.options.iter_present() {
+            let _ = writeln!(out, "  {}", option);
+        }
+        out
+    }
+
+    /// Increments the hop count, fails above [`DEFAULT_MAX_HOPS`]
+    pub fn increment_hops(&mut self) -> DhcpResult<()> {
+        self.increment_hops_with_limit(DEFAULT_MAX_HOPS)
+    }
+
+    /// Increments the hop count, fails if the result would exceed `max_hops`
+    pub fn increment_hops_with_limit(&mut self, max_hops: u8) -> DhcpResult<()> {
+        match self.hops.checked_add(1) {
+            Some(hops) if hops <= max_hops => {
+                self.hops = hops;
+                Ok(())
+            }
+            _ => Err(DhcpError::HopLimitExceeded)
+        }
+    }
+
+    /// Prepares a client packet to be forwarded by a relay agent
+    ///
+    /// Sets the gateway address if not already set by a previous relay and increments the hops.
+    pub fn relay_forward(&mut self, relay_address: Ipv4Addr) -> DhcpResult<()> {
+        self.increment_hops()?;
+        if self.gateway.is_unspecified() {
+            self.gateway = relay_address;
+        }
+        Ok(())
+    }
+
     pub fn into_bytes_with_server_ips(self, ips: Vec<Ipv4Addr>) -> HashMap<Ipv4Addr, Vec<u8>> {
         let mut bytes:Vec<u8> = self.into();
         ips.into_iter().map(|ip|{
@@ -420,54 +878,257 @@ impl DhcpPacket {
             (ip, bytes.clone())
         }).collect()
     }
+
+    /// Serializes the packet once and patches client hardware and your address for each client
+    pub fn into_bytes_with_clients(self, clients: Vec<(MacAddress, Ipv4Addr)>) -> Vec<Vec<u8>> {
+        let mut bytes: Vec<u8> = self.into();
+        clients.into_iter().map(|(mac, ip)| {
+            bytes[HARDWARE_LENGTH] = mac.size();
+            bytes.splice(CLIENT_HARDWARE, bytes_fill_zeroes(mac.as_bytes(), 16));
+            bytes.splice(YOUR_IP, ip.octets());
+            bytes.clone()
+        }).collect()
+    }
+
+    /// Starts a [`DhcpPacketBuilder`] with all fields unset
+    pub fn builder() -> DhcpPacketBuilder {
+        DhcpPacketBuilder::default()
+    }
+}
+
+/// Readable construction of a [`DhcpPacket`] instead of the positional [`DhcpPacket::new`]
+///
+/// Unset fields default to a unicast `BootRequest` on ethernet with unspecified addresses,
+/// a zero hardware address, no options and the DHCP cookie.
+#[derive(Debug)]
+pub struct DhcpPacketBuilder {
+    packet: DhcpPacket,
+}
+
+impl Default for DhcpPacketBuilder {
+    fn default() -> Self {
+        Self {
+            packet: DhcpPacket::new(
+                MessageOperation::BootRequest,
+                HardwareAddressType::Ethernet,
+                0,
+                0,
+                0,
+                Flags::Unicast,
+                Ipv4Addr::UNSPECIFIED,
+                Ipv4Addr::UNSPECIFIED,
+                Ipv4Addr::UNSPECIFIED,
+                Ipv4Addr::UNSPECIFIED,
+                MacAddr6::nil(),
+                AsciiString::new(),
+                AsciiString::new(),
+                DhcpOptions::new(),
+            )
+        }
+    }
+}
+
+impl DhcpPacketBuilder {
+    pub fn operation(mut self, operation: MessageOperation) -> Self {
+        self.packet.operation = operation;
+        self
+    }
+    pub fn hardware_type(mut self, hardware_type: HardwareAddressType) -> Self {
+        self.packet.hardware_type = hardware_type;
+        self
+    }
+    pub fn hops(mut self, hops: u8) -> Self {
+        self.packet.hops = hops;
+        self
+    }
+    pub fn transaction_id(mut self, transaction_id: u32) -> Self {
+        self.packet.transaction_id = transaction_id;
+        self
+    }
+    pub fn seconds(mut self, seconds: u16) -> Self {
+        self.packet.seconds = seconds;
+        self
+    }
+    pub fn flags(mut self, flags: Flags) -> Self {
+        self.packet.flags = flags;
+        self
+    }
+    pub fn client<I: Into<Ipv4Addr>>(mut self, client: I) -> Self {
+        self.packet.client = client.into();
+        self
+    }
+    pub fn your<I: Into<Ipv4Addr>>(mut self, your: I) -> Self {
+        self.packet.your = your.into();
+        self
+    }
+    pub fn server<I: Into<Ipv4Addr>>(mut self, server: I) -> Self {
+        self.packet.server = server.into();
+        self
+    }
+    pub fn gateway<I: Into<Ipv4Addr>>(mut self, gateway: I) -> Self {
+        self.packet.gateway = gateway.into();
+        self
+    }
+    pub fn client_hardware<C: Into<MacAddress>>(mut self, client_hardware: C) -> Self {
+        self.packet.client_hardware = client_hardware.into();
+        self
+    }
+    pub fn server_hostname<S: Into<AsciiString>>(mut self, server_hostname: S) -> Self {
+        self.packet.server_hostname = server_hostname.into();
+        self
+    }
+    pub fn filename<S: Into<AsciiString>>(mut self, filename: S) -> Self {
+        self.packet.filename = filename.into();
+        self
+    }
+    /// Adds the option, replacing an option with the same tag
+    pub fn option(mut self, option: DhcpOption) -> Self {
+        self.packet.options.upsert(option);
+        self
+    }
+    /// Replaces all options
+    pub fn options<O: Into<DhcpOptions>>(mut self, options: O) -> Self {
+        self.packet.options = options.into();
+        self
+    }
+
+    pub fn build(self) -> DhcpPacket {
+        self.packet
+    }
 }
 
 
 impl From<DhcpPacket> for Vec<u8> {
     fn from(p: DhcpPacket) -> Self {
-        let mut bytes = vec![p.operation.into(),
-                             p.hardware_type.into(),
-                             p.client_hardware.size(),
-                             p.hops];
+        let mut bytes = Vec::with_capacity(p.serialized_len());
+        p.write_to(&mut bytes);
+        bytes
+    }
+}
 
-        bytes.extend_from_slice(&p.transaction_id.to_be_bytes());
-        bytes.extend_from_slice(&p.seconds.to_be_bytes());
-        bytes.extend_from_slice(p.flags.into());
+impl DhcpPacket {
+    /// Appends the serialized packet to `buf`
+    ///
+    /// Same bytes as `Into<Vec<u8>>`, but a cleared buffer can be reused across packets.
+    pub fn write_to(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&[self.operation.clone().into(),
+            self.hardware_type.clone().into(),
+            self.hardware_length(),
+            self.hops]);
+
+        buf.extend_from_slice(&self.transaction_id.to_be_bytes());
+        buf.extend_from_slice(&self.seconds.to_be_bytes());
+        buf.extend_from_slice(self.flags.clone().into());
 
-        bytes.extend_from_slice(&p.client.octets());
-        bytes.extend_from_slice(&p.your.octets());
-        bytes.extend_from_slice(&p.server.octets());
-        bytes.extend_from_slice(&p.gateway.octets());
+        buf.extend_from_slice(&self.client.octets());
+        buf.extend_from_slice(&self.your.octets());
+        buf.extend_from_slice(&self.server.octets());
+        buf.extend_from_slice(&self.gateway.octets());
 
-        bytes.extend_from_slice(&p.client_hardware.as_bytes());
+        // chaddr is always 16 bytes, whatever the hardware address length
+        write_fill_zeroes(buf, self.client_hardware.as_bytes(), 16);
 
-        // mac padding
-        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0]);
-        if p.client_hardware.is_v6() {
-            bytes.extend_from_slice(&[0, 0]);
+        write_fill_zeroes(buf, self.server_hostname.as_bytes(), 64);
+        write_fill_zeroes(buf, self.filename.as_bytes(), 128);
+        match self.cookie {
+            Cookie::Dhcp => {
+                buf.extend_from_slice(self.cookie.as_bytes());
+                self.options.write_to(buf);
+                if self.options.option(END).is_none() {
+                    buf.push(END);
+                }
+            }
+            // the raw vendor area already holds the cookie bytes, if any
+            Cookie::None | Cookie::Other(_) => buf.extend_from_slice(&self.vendor),
         }
+    }
+}
 
-        bytes.extend_from_slice(bytes_fill_zeroes(p.server_hostname.as_bytes(), 64).as_slice());
-        bytes.extend_from_slice(bytes_fill_zeroes(p.filename.as_bytes(), 128).as_slice());
-        bytes.extend_from_slice(p.cookie.into());
-        bytes.extend_from_slice(&p.options.to_bytes());
-        bytes
+
+/// BOOTP fields carrying options according to the option overload (52), `file` is read before `sname` (rfc 2131 4.1)
+fn overloaded_fields(options: &DhcpOptions) -> &'static [Range<usize>] {
+    match options.option(OPTION_OVERLOAD) {
+        Some(DhcpOption::OptionOverload(Overload::File)) => &[FILENAME],
+        Some(DhcpOption::OptionOverload(Overload::Sname)) => &[SERVER_HOSTNAME],
+        Some(DhcpOption::OptionOverload(Overload::Both)) => &[FILENAME, SERVER_HOSTNAME],
+        _ => &[]
     }
 }
 
+/// Raw options of an overloaded field, a field left zeroed counts as empty
+fn overloaded_raw_options(field: &[u8]) -> DhcpResult<RawOptions> {
+    if field.iter().all(|b| *b == PAD) {
+        return Ok(vec![]);
+    }
+    DhcpOptions::raw_from_bytes(field).map(|(raw, _)| raw)
+}
 
 impl TryFrom<&[u8]> for DhcpPacket {
     type Error = DhcpError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse_with(value, &ParseOptions::default())
+    }
+}
+
+impl DhcpPacket {
+    /// Parses a packet like `try_from` using the given parser configuration
+    pub fn parse_with(value: &[u8], parse_options: &ParseOptions) -> DhcpResult<Self> {
+        Self::parse_with_len(value, parse_options).map(|(packet, _)| packet)
+    }
+
+    /// Parses a packet at the start of `value` and returns the bytes following it
+    ///
+    /// For DHCP packets the tail starts right after the `End` option. A BOOTP packet
+    /// consumes the whole buffer since its vendor area has no terminator.
+    pub fn parse_prefix(value: &[u8]) -> DhcpResult<(Self, &[u8])> {
+        let (packet, length) = Self::parse_with_len(value, &ParseOptions::default())?;
+        Ok((packet, &value[length..]))
+    }
+
+    /// Parses a packet and returns the number of bytes consumed
+    fn parse_with_len(value: &[u8], parse_options: &ParseOptions) -> DhcpResult<(Self, usize)> {
         let packet_length = value.len();
-        if packet_length < OPTIONS.start {
+        if packet_length < COOKIE.start {
             return Err(DhcpError::InvalidPacketLength(packet_length as u8));
         }
 
-        Ok(DhcpPacket {
+        // BOOTP packets may lack the cookie or the whole vendor area
+        let cookie = match value.get(COOKIE) {
+            Some(cookie) => Cookie::try_from(cookie)?,
+            None => Cookie::None,
+        };
+
+        let mut overloaded: &[Range<usize>] = &[];
+        let (options, raw_options, vendor, length) = if cookie == Cookie::Dhcp {
+            let (options, mut raw_options, options_length) = DhcpOptions::from_bytes_with_raw(&value[OPTIONS], parse_options)?;
+            let length = OPTIONS.start + options_length;
+            overloaded = overloaded_fields(&options);
+            if overloaded.is_empty() {
+                (options, Some(raw_options), vec![], length)
+            } else {
+                for field in overloaded {
+                    raw_options.extend(overloaded_raw_options(&value[field.clone()])?);
+                }
+                // the merged options are serialized into the options field only, so the overload no longer applies
+                raw_options.retain(|(tag, _)| *tag != OPTION_OVERLOAD);
+                (DhcpOptions::from_raw(&raw_options, parse_options)?, Some(raw_options), vec![], length)
+            }
+        } else {
+            (DhcpOptions::new(), None, value[VENDOR].to_vec(), packet_length)
+        };
+        let ascii_field = |field: Range<usize>| if overloaded.contains(&field) {
+            AsciiString::new()
+        } else {
+            value[field].iter().filter_map(byte_to_char).collect::<AsciiString>()
+        };
+
+        let hardware_type = HardwareAddressType::try_from(&value[HARDWARE_TYPE])?;
+        let no_hardware_address = matches!(hardware_type, HardwareAddressType::Other(_));
+
+        let packet = DhcpPacket {
             operation: MessageOperation::try_from(&value[OP])?,
-            hardware_type: HardwareAddressType::try_from(&value[HARDWARE_TYPE])?,
+            hardware_type,
             hops: value[HOPS],
             transaction_id: u32::from_be_bytes(value[XID].try_into().map_err(|_| DhcpError::TransactionIdParseError)?),
             seconds: u16::from_be_bytes(value[SECONDS].try_into().map_err(|_| DhcpError::SecondsParseError)?),
@@ -476,7 +1137,7 @@ impl TryFrom<&[u8]> for DhcpPacket {
             your: ipv4_from_bytes(&value[YOUR_IP], DhcpError::YourAddressParseError)?,
             server: ipv4_from_bytes(&value[SERVER_IP], DhcpError::ServerAddressParseError)?,
             gateway: ipv4_from_bytes(&value[GATEWAY_IP], DhcpError::GatewayAddressParseError)?,
-            client_hardware: match value[2] {
+            client_hardware: match value[HARDWARE_LENGTH] {
                 6 => {
                     let bytes: [u8; 6] = value[CLIENT_HARDWARE_6].try_into().map_err(|_| DhcpError::HardwareAddressParseError)?;
                     MacAddr::from(bytes).into()
@@ -485,24 +1146,159 @@ impl TryFrom<&[u8]> for DhcpPacket {
                     let bytes: [u8; 8] = value[CLIENT_HARDWARE_8].try_into().map_err(|_| DhcpError::HardwareAddressParseError)?;
                     MacAddr::from(bytes).into()
                 }
+                0 if no_hardware_address => MacAddr6::nil().into(),
                 _ => return Err(DhcpError::HardwareAddressParseError)
             },
-            server_hostname: value[SERVER_HOSTNAME].iter().filter_map(byte_to_char).collect::<AsciiString>(),
-            filename: value[FILENAME].iter().filter_map(byte_to_char).collect::<AsciiString>(),
-            cookie: value[COOKIE].try_into()?,
-            options: DhcpOptions::from_bytes(&value[OPTIONS])?,
-        })
+            server_hostname: ascii_field(SERVER_HOSTNAME),
+            filename: ascii_field(FILENAME),
+            cookie,
+            options,
+            raw_options,
+            vendor,
+        };
+        Ok((packet, length))
+    }
+
+    /// Parses a DHCP packet, unlike `try_from` a BOOTP packet is rejected
+    ///
+    /// Fails with [`DhcpError::DhcpMessagePacketError`] if the DHCP cookie or the message type
+    /// option (53) is missing.
+    pub fn try_from_dhcp(value: &[u8]) -> DhcpResult<Self> {
+        let packet = Self::try_from(value)?;
+        if packet.cookie != Cookie::Dhcp || packet.message_type_value().is_none() {
+            return Err(DhcpError::DhcpMessagePacketError);
+        }
+        Ok(packet)
+    }
+}
+
+/// JSON friendly representation of a [`DhcpPacket`]
+///
+/// Provides a stable schema for `with_serde` users:
+/// * `transaction_id` as hex string, e.g. `"0x00003d1d"`
+/// * ip addresses in dotted notation
+/// * `client_hardware` as colon separated mac address
+/// * `message_type` by name, taken from the options and ignored on conversion back
+/// * `cookie` by name, `{"Other": [..]}` for a non-standard cookie
+/// * `vendor` as byte list, the raw vendor area of a BOOTP packet, omitted if empty
+/// * `options` as list of objects keyed by option name
+#[cfg(feature = "with_serde")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DhcpPacketJson {
+    pub operation: MessageOperation,
+    pub hardware_type: HardwareAddressType,
+    pub hops: u8,
+    pub transaction_id: String,
+    pub seconds: u16,
+    pub flags: Flags,
+    pub client: Ipv4Addr,
+    pub your: Ipv4Addr,
+    pub server: Ipv4Addr,
+    pub gateway: Ipv4Addr,
+    pub client_hardware: String,
+    pub server_hostname: String,
+    pub filename: String,
+    pub message_type: Option<MessageType>,
+    pub cookie: Cookie,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vendor: Vec<u8>,
+    pub options: Vec<DhcpOption>,
+}
+
+#[cfg(feature = "with_serde")]
+impl From<DhcpPacket> for DhcpPacketJson {
+    fn from(p: DhcpPacket) -> Self {
+        let message_type = match p.message_type() {
+            Some(DhcpOption::MessageType(t)) => Some(t.clone()),
+            _ => None
+        };
+
+        Self {
+            operation: p.operation,
+            hardware_type: p.hardware_type,
+            hops: p.hops,
+            transaction_id: format!("{:#010x}", p.transaction_id),
+            seconds: p.seconds,
+            flags: p.flags,
+            client: p.client,
+            your: p.your,
+            server: p.server,
+            gateway: p.gateway,
+            client_hardware: p.client_hardware.to_string(),
+            server_hostname: p.server_hostname.to_string(),
+            filename: p.filename.to_string(),
+            message_type,
+            cookie: p.cookie,
+            vendor: p.vendor,
+            options: p.options.into_iter().flatten().collect(),
+        }
+    }
+}
+
+#[cfg(feature = "with_serde")]
+impl TryFrom<DhcpPacketJson> for DhcpPacket {
+    type Error = DhcpError;
+
+    fn try_from(p: DhcpPacketJson) -> Result<Self, Self::Error> {
+        let transaction_id = p.transaction_id.strip_prefix("0x")
+            .and_then(|xid| u32::from_str_radix(xid, 16).ok())
+            .ok_or(DhcpError::TransactionIdParseError)?;
+        let client_hardware: MacAddr = p.client_hardware.parse().map_err(|_| DhcpError::HardwareAddressParseError)?;
+
+        let mut packet = DhcpPacket::new(
+            p.operation,
+            p.hardware_type,
+            p.hops,
+            transaction_id,
+            p.seconds,
+            p.flags,
+            p.client,
+            p.your,
+            p.server,
+            p.gateway,
+            client_hardware,
+            AsciiString::from_ascii(p.server_hostname).map_err(|_| DhcpError::ServerHostnameParseError)?,
+            AsciiString::from_ascii(p.filename).map_err(|_| DhcpError::FilenameParseError)?,
+            p.options,
+        );
+        packet.cookie = p.cookie;
+        packet.vendor = p.vendor;
+        Ok(packet)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::dhcp::{DhcpPacket, HardwareAddressType, Flags, Cookie};
-    use std::convert::{TryFrom, TryInto};
+    use crate::dhcp::{DhcpPacket, HardwareAddressType, Flags, Cookie, MessageOperation, LeaseKey, DEFAULT_MAX_HOPS, COOKIE, FILENAME,
+                      SERVER_HOSTNAME, is_dhcp_packet, from_udp_datagram};
+    use crate::error::DhcpError;
+    use crate::option::{DhcpOption, DhcpOptions, ClientIdentifier, MessageType, Overload, ParseOptions, END, HOST_NAME, MESSAGE,
+                        OPTION_OVERLOAD};
+    use std::convert::TryFrom;
     use std::net::Ipv4Addr;
-    use macaddr::MacAddr;
+    use macaddr::{MacAddr, MacAddr6, MacAddr8};
+    use ascii::AsciiString;
     use std::str::FromStr;
 
+    fn packet_with_options(mac: MacAddr6, options: Vec<DhcpOption>) -> DhcpPacket {
+        DhcpPacket::new(
+            MessageOperation::BootRequest,
+            HardwareAddressType::Ethernet,
+            0,
+            1,
+            0,
+            Flags::Unicast,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            mac,
+            ascii::AsciiString::new(),
+            ascii::AsciiString::new(),
+            DhcpOptions::from(options),
+        )
+    }
+
     #[test]
     fn test_without_options() {
         let from_bytes: &[u8] = include_bytes!("../client_request.bin");
@@ -523,7 +1319,691 @@ mod tests {
         assert_eq!(packet.cookie, Cookie::Dhcp);
         assert_eq!(packet.options.options().len(), 5);
 
-        let to_bytes: Vec<u8> = packet.try_into().unwrap();
+        let to_bytes: Vec<u8> = packet.into();
         assert_eq!(to_bytes[..240], from_bytes[..240]);
     }
+
+    #[test]
+    fn test_lease_key_zero_mac() {
+        let packet = packet_with_options(MacAddr6::nil(), vec![]);
+        assert!(packet.client_hardware.is_zero());
+        assert_eq!(packet.lease_key(), None);
+
+        let id = ClientIdentifier::new(1, vec![1, 2, 3, 4, 5, 6]);
+        let packet = packet_with_options(MacAddr6::nil(), vec![DhcpOption::ClientIdentifier(id.clone())]);
+        assert_eq!(packet.lease_key(), Some(LeaseKey::ClientIdentifier(id)));
+    }
+
+    #[test]
+    fn test_lease_key_mac() {
+        let mac = MacAddr6::new(0, 1, 2, 3, 4, 5);
+        let packet = packet_with_options(mac, vec![DhcpOption::ClientIdentifier(ClientIdentifier::new(0, vec![9]))]);
+        assert!(!packet.client_hardware.is_zero());
+        assert_eq!(packet.lease_key(), Some(LeaseKey::HardwareAddress(mac.into())));
+    }
+
+    #[test]
+    fn test_raw_options_forwarding() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes[..240].to_vec();
+        let options: &[u8] = &[53, 1, 1, 224, 3, 1, 2, 3, 50, 4, 192, 168, 0, 1];
+        bytes.extend_from_slice(options);
+        bytes.push(255);
+
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        let raw = packet.raw_options();
+        assert_eq!(raw, vec![(53, vec![1]), (224, vec![1, 2, 3]), (50, vec![192, 168, 0, 1])]);
+
+        let forwarded: Vec<u8> = raw.into_iter().flat_map(|(tag, data)| {
+            let mut option = vec![tag, data.len() as u8];
+            option.extend(data);
+            option
+        }).collect();
+        assert_eq!(forwarded, options);
+    }
+
+    #[test]
+    fn test_client_max_message_size() {
+        let packet = packet_with_options(MacAddr6::nil(), vec![DhcpOption::MaximumDhcpMessageSize(1500)]);
+        assert_eq!(packet.client_max_message_size(), Some(1500));
+
+        let packet = packet_with_options(MacAddr6::nil(), vec![]);
+        assert_eq!(packet.client_max_message_size(), None);
+    }
+
+    #[test]
+    fn test_increment_hops() {
+        let mut packet = packet_with_options(MacAddr6::nil(), vec![]);
+        packet.increment_hops().unwrap();
+        assert_eq!(packet.hops, 1);
+
+        packet.hops = DEFAULT_MAX_HOPS;
+        assert!(matches!(packet.increment_hops(), Err(DhcpError::HopLimitExceeded)));
+        assert_eq!(packet.hops, DEFAULT_MAX_HOPS);
+
+        packet.hops = 2;
+        assert!(matches!(packet.increment_hops_with_limit(2), Err(DhcpError::HopLimitExceeded)));
+    }
+
+    #[test]
+    fn test_relay_forward() {
+        let mut packet = packet_with_options(MacAddr6::nil(), vec![]);
+        packet.relay_forward(Ipv4Addr::new(10, 0, 0, 1)).unwrap();
+        packet.relay_forward(Ipv4Addr::new(10, 0, 1, 1)).unwrap();
+        assert_eq!(packet.hops, 2);
+        assert_eq!(packet.gateway, Ipv4Addr::new(10, 0, 0, 1));
+    }
+
+    #[cfg(feature = "with_serde")]
+    #[test]
+    fn test_json_round_trip() {
+        use crate::dhcp::DhcpPacketJson;
+
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let packet = DhcpPacket::try_from(from_bytes).unwrap();
+
+        let json = serde_json::to_string(&DhcpPacketJson::from(packet)).unwrap();
+        assert!(json.contains(r#""transaction_id":"0x00003d1d""#));
+        assert!(json.contains(r#""client":"0.0.0.0""#));
+        assert!(json.contains(r#""message_type":"Discover""#));
+        assert!(json.contains(r#""cookie":"Dhcp""#));
+        assert!(!json.contains(r#""vendor""#));
+
+        let json: DhcpPacketJson = serde_json::from_str(&json).unwrap();
+        let packet = DhcpPacket::try_from(json).unwrap();
+        let to_bytes: Vec<u8> = packet.into();
+        assert_eq!(to_bytes[..240], from_bytes[..240]);
+
+        let reparsed = DhcpPacket::try_from(to_bytes.as_slice()).unwrap();
+        let original = DhcpPacket::try_from(from_bytes).unwrap();
+        assert_eq!(reparsed.options().options(), original.options().options());
+    }
+
+    #[test]
+    #[cfg(feature = "with_serde")]
+    fn test_json_round_trip_full_packet() {
+        use crate::dhcp::DhcpPacketJson;
+        use crate::fixtures::full_packet;
+
+        let json = serde_json::to_string(&DhcpPacketJson::from(full_packet())).unwrap();
+        let json: DhcpPacketJson = serde_json::from_str(&json).unwrap();
+        let packet = DhcpPacket::try_from(json).unwrap();
+
+        assert_eq!(packet.options().options(), full_packet().options().options());
+        assert_eq!(Vec::<u8>::from(packet), Vec::<u8>::from(full_packet()));
+    }
+
+    #[test]
+    #[cfg(feature = "with_serde")]
+    fn test_json_round_trip_bootp() {
+        use crate::dhcp::DhcpPacketJson;
+
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes[..236].to_vec();
+        bytes.extend_from_slice(&[0, 0, 0, 0, 1, 2, 3, 4]);
+
+        let json = serde_json::to_string(&DhcpPacketJson::from(DhcpPacket::try_from(bytes.as_slice()).unwrap())).unwrap();
+        assert!(json.contains(r#""cookie":"None""#));
+        assert!(json.contains(r#""vendor":[0,0,0,0,1,2,3,4]"#));
+
+        let json: DhcpPacketJson = serde_json::from_str(&json).unwrap();
+        let packet = DhcpPacket::try_from(json).unwrap();
+        assert_eq!(Vec::<u8>::from(packet), bytes);
+    }
+
+    #[test]
+    fn test_into_bytes_with_clients() {
+        let packet = packet_with_options(MacAddr6::nil(), vec![DhcpOption::End]);
+        let first = MacAddr6::new(0, 1, 2, 3, 4, 5);
+        let second = MacAddr6::new(6, 7, 8, 9, 10, 11);
+
+        let variants = packet.into_bytes_with_clients(vec![
+            (first.into(), Ipv4Addr::new(10, 0, 0, 1)),
+            (second.into(), Ipv4Addr::new(10, 0, 0, 2)),
+        ]);
+        assert_eq!(variants.len(), 2);
+
+        let parsed = DhcpPacket::try_from(variants[0].as_slice()).unwrap();
+        assert_eq!(parsed.client_hardware, first.into());
+        assert_eq!(parsed.your, Ipv4Addr::new(10, 0, 0, 1));
+
+        let parsed = DhcpPacket::try_from(variants[1].as_slice()).unwrap();
+        assert_eq!(parsed.client_hardware, second.into());
+        assert_eq!(parsed.your, Ipv4Addr::new(10, 0, 0, 2));
+    }
+
+    #[test]
+    fn test_bootp_vendor_data() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes[..236].to_vec();
+        let mut vendor = vec![0; 64];
+        vendor[4..8].copy_from_slice(&[1, 2, 3, 4]);
+        bytes.extend_from_slice(&vendor);
+
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(packet.cookie, Cookie::None);
+        assert_eq!(packet.bootp_vendor_data(), Some(vendor.as_slice()));
+        assert!(packet.options().options().is_empty());
+
+        let to_bytes: Vec<u8> = packet.into();
+        assert_eq!(to_bytes, bytes);
+
+        let packet = DhcpPacket::try_from(from_bytes).unwrap();
+        assert_eq!(packet.bootp_vendor_data(), None);
+    }
+
+    #[test]
+    fn test_other_cookie() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes.to_vec();
+        bytes[236..240].copy_from_slice(&[1, 2, 3, 4]);
+
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(packet.cookie, Cookie::Other([1, 2, 3, 4]));
+        assert_eq!(packet.cookie().as_bytes(), &[1, 2, 3, 4]);
+        let cookie: &[u8] = Cookie::Other([1, 2, 3, 4]).into();
+        assert_eq!(cookie, packet.cookie().as_bytes());
+        assert_eq!(packet.bootp_vendor_data(), Some(&bytes[236..]));
+        assert!(packet.options().options().is_empty());
+
+        let to_bytes: Vec<u8> = packet.into();
+        assert_eq!(to_bytes, bytes);
+    }
+
+    #[test]
+    fn test_other_cookie_kept_on_modification() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes.to_vec();
+        bytes[236..240].copy_from_slice(&[0x63, 0x82, 0x53, 0x64]);
+
+        let mut packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert!(DhcpPacket::try_from_dhcp(&bytes).is_err());
+        packet.set_your(Ipv4Addr::new(10, 0, 0, 2));
+
+        let to_bytes: Vec<u8> = packet.into();
+        assert_eq!(&to_bytes[16..20], &[10, 0, 0, 2]);
+        assert_eq!(&to_bytes[236..240], &[0x63, 0x82, 0x53, 0x64]);
+        assert_eq!(&to_bytes[240..], &bytes[240..]);
+    }
+
+    #[test]
+    fn test_next_server_and_server_identifier() {
+        let mut packet = packet_with_options(MacAddr6::nil(), vec![DhcpOption::ServerIdentifier(Ipv4Addr::new(10, 0, 0, 1))]);
+        packet.server = Ipv4Addr::new(10, 0, 0, 2);
+
+        assert_eq!(packet.server_identifier_ip(), Some(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(packet.next_server(), Ipv4Addr::new(10, 0, 0, 2));
+    }
+
+    #[test]
+    fn test_parse_with_max_string_len() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes[..240].to_vec();
+        bytes.extend_from_slice(&[12, 200]);
+        bytes.extend_from_slice(&[b'a'; 200]);
+        bytes.push(255);
+
+        assert!(DhcpPacket::try_from(bytes.as_slice()).is_ok());
+
+        let strict = ParseOptions { max_string_len: Some(63) };
+        assert!(matches!(DhcpPacket::parse_with(bytes.as_slice(), &strict), Err(DhcpError::OptionInvalidValueError(12))));
+    }
+
+    #[test]
+    fn test_is_dhcp_packet() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        assert!(is_dhcp_packet(from_bytes));
+        assert!(!is_dhcp_packet(&from_bytes[..239]));
+
+        let mut seed: u32 = 0x1234_5678;
+        let random: Vec<u8> = (0..300).map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 16) as u8
+        }).collect();
+        assert!(!is_dhcp_packet(&random));
+    }
+
+    #[test]
+    fn test_options_as_map() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let packet = DhcpPacket::try_from(from_bytes).unwrap();
+
+        let map = packet.options().as_map();
+        let tags: Vec<u8> = packet.options().options().iter().map(|o| o.tag()).collect();
+        assert_eq!(map.keys().copied().collect::<Vec<u8>>(), tags);
+        assert!(map.values().all(|o| packet.option(o.tag()) == Some(*o)));
+    }
+
+    #[test]
+    fn test_relay_link_selection() {
+        use crate::option::RelayAgentInformationSubOption;
+
+        let packet = packet_with_options(MacAddr6::nil(), vec![DhcpOption::RelayAgentInformation(vec![
+            RelayAgentInformationSubOption::AgentCircuit(vec![1]),
+            RelayAgentInformationSubOption::LinkSelection(Ipv4Addr::new(10, 0, 1, 0)),
+        ])]);
+        assert_eq!(packet.relay_link_selection(), Some(Ipv4Addr::new(10, 0, 1, 0)));
+
+        let packet = packet_with_options(MacAddr6::nil(), vec![]);
+        assert_eq!(packet.relay_link_selection(), None);
+    }
+
+    #[test]
+    fn test_init_reboot_request() {
+        use crate::option::{REQUESTED_IP_ADDRESS, SERVER_IDENTIFIER, PARAMETER_REQUEST_LIST};
+
+        let requested = Ipv4Addr::new(192, 168, 1, 10);
+        let packet = DhcpPacket::init_reboot_request(MacAddr6::new(0, 1, 2, 3, 4, 5), requested, 7,
+                                                     Some(vec![1, 3, 6]));
+
+        assert_eq!(packet.option(REQUESTED_IP_ADDRESS), Some(&DhcpOption::RequestedIpAddress(requested)));
+        assert!(packet.option(SERVER_IDENTIFIER).is_none());
+        assert_eq!(packet.option(PARAMETER_REQUEST_LIST), Some(&DhcpOption::ParameterRequestList(vec![1, 3, 6])));
+        assert_eq!(packet.client, Ipv4Addr::UNSPECIFIED);
+        assert_eq!(packet.transaction_id, 7);
+    }
+
+    #[test]
+    fn test_serialized_len() {
+        use crate::fixtures::full_packet;
+
+        let mut without_end = full_packet();
+        without_end.options_mut().remove(END);
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+
+        let fixtures = vec![
+            full_packet(),
+            without_end,
+            DhcpPacket::try_from(from_bytes).unwrap(),
+            DhcpPacket::init_reboot_request(MacAddr6::nil(), Ipv4Addr::new(10, 0, 0, 1), 1, None),
+            packet_with_options(MacAddr6::nil(), vec![]),
+        ];
+
+        for packet in fixtures {
+            assert!(packet.options().options().iter().all(|o| o.encoded_len() == o.to_bytes().len()));
+            let serialized_len = packet.serialized_len();
+            let options_len: usize = packet.options().options().iter()
+                .filter(|o| !matches!(o, DhcpOption::End))
+                .map(|o| o.encoded_len())
+                .sum();
+            let bytes: Vec<u8> = packet.into();
+
+            assert_eq!(serialized_len, bytes.len());
+            assert_eq!(bytes.len() - 240, options_len + 1);
+            assert_eq!(bytes.last(), Some(&END));
+        }
+    }
+
+    #[test]
+    fn test_message_type_value() {
+        let mac = MacAddr6::from_str("00:11:22:33:44:55").unwrap();
+        let bytes: Vec<u8> = packet_with_options(mac, vec![DhcpOption::MessageType(MessageType::Discover)]).into();
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(packet.message_type_value(), Some(MessageType::Discover));
+        assert_eq!(packet.options().message_type_value(), Some(MessageType::Discover));
+
+        let bytes: Vec<u8> = packet_with_options(mac, vec![]).into();
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(packet.message_type_value(), None);
+    }
+
+    #[test]
+    fn test_setters() {
+        let mac = MacAddr6::from_str("00:11:22:33:44:55").unwrap();
+        let mut packet = packet_with_options(mac, vec![]);
+        packet.set_transaction_id(0x01020304);
+        packet.set_flags(Flags::Broadcast);
+        packet.set_client(Ipv4Addr::new(10, 0, 0, 1));
+        packet.set_your(Ipv4Addr::new(10, 0, 0, 2));
+        packet.set_server(Ipv4Addr::new(10, 0, 0, 3));
+        packet.set_gateway(Ipv4Addr::new(10, 0, 0, 4));
+
+        let bytes: Vec<u8> = packet.into();
+        assert_eq!(&bytes[4..8], &[1, 2, 3, 4]);
+        assert_eq!(&bytes[10..12], &[0x80, 0]);
+        assert_eq!(&bytes[12..16], &[10, 0, 0, 1]);
+        assert_eq!(&bytes[16..20], &[10, 0, 0, 2]);
+        assert_eq!(&bytes[20..24], &[10, 0, 0, 3]);
+        assert_eq!(&bytes[24..28], &[10, 0, 0, 4]);
+
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(packet.transaction_id(), 0x01020304);
+        assert_eq!(packet.flags(), &Flags::Broadcast);
+        assert_eq!(packet.gateway(), &Ipv4Addr::new(10, 0, 0, 4));
+    }
+
+    #[test]
+    fn test_builder() {
+        let mac = MacAddr6::from_str("00:11:22:33:44:55").unwrap();
+        let packet = DhcpPacket::builder()
+            .transaction_id(0x01020304)
+            .flags(Flags::Broadcast)
+            .client_hardware(mac)
+            .option(DhcpOption::MessageType(MessageType::Discover))
+            .option(DhcpOption::ParameterRequestList(vec![1, 3, 6]))
+            .build();
+        let mut expected = packet_with_options(mac, vec![
+            DhcpOption::MessageType(MessageType::Discover),
+            DhcpOption::ParameterRequestList(vec![1, 3, 6]),
+        ]);
+        expected.set_transaction_id(0x01020304);
+        expected.set_flags(Flags::Broadcast);
+        assert_eq!(packet, expected);
+
+        let bytes: Vec<u8> = packet.into();
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert!(matches!(packet.operation(), MessageOperation::BootRequest));
+        assert_eq!(packet.hardware_type(), &HardwareAddressType::Ethernet);
+        assert_eq!(packet.transaction_id(), 0x01020304);
+        assert_eq!(packet.your(), &Ipv4Addr::UNSPECIFIED);
+        assert_eq!(packet.client_hardware(), &MacAddr::from(mac));
+        assert_eq!(packet.cookie(), &Cookie::Dhcp);
+        assert_eq!(packet.message_type_value(), Some(MessageType::Discover));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mac = MacAddr6::from_str("00:11:22:33:44:55").unwrap();
+        let discover = packet_with_options(mac, vec![DhcpOption::MessageType(MessageType::Discover)]);
+        assert!(discover.validate().is_ok());
+
+        let request = packet_with_options(mac, vec![DhcpOption::ServerIdentifier(Ipv4Addr::new(10, 0, 0, 1))]);
+        assert!(matches!(request.validate(), Err(DhcpError::PacketValidation(_))));
+
+        let request = packet_with_options(mac, vec![
+            DhcpOption::MessageType(MessageType::Request),
+            DhcpOption::ServerIdentifier(Ipv4Addr::new(10, 0, 0, 1)),
+        ]);
+        assert!(matches!(request.validate(), Err(DhcpError::PacketValidation(_))));
+
+        let request = packet_with_options(mac, vec![
+            DhcpOption::MessageType(MessageType::Request),
+            DhcpOption::ServerIdentifier(Ipv4Addr::new(10, 0, 0, 1)),
+            DhcpOption::RequestedIpAddress(Ipv4Addr::new(10, 0, 0, 2)),
+        ]);
+        assert!(request.validate().is_ok());
+
+        let offer = packet_with_options(mac, vec![DhcpOption::MessageType(MessageType::Offer)]);
+        assert!(matches!(offer.validate(), Err(DhcpError::PacketValidation(_))));
+    }
+
+    #[test]
+    fn test_try_from_dhcp() {
+        let mac = MacAddr6::from_str("00:11:22:33:44:55").unwrap();
+        let discover: Vec<u8> = packet_with_options(mac, vec![DhcpOption::MessageType(MessageType::Discover)]).into();
+        assert!(DhcpPacket::try_from_dhcp(&discover).is_ok());
+
+        let bootp: Vec<u8> = packet_with_options(mac, vec![]).into();
+        assert!(matches!(DhcpPacket::try_from_dhcp(&bootp), Err(DhcpError::DhcpMessagePacketError)));
+        assert!(DhcpPacket::try_from(bootp.as_slice()).is_ok());
+
+        let mut no_cookie = bootp.clone();
+        no_cookie.truncate(COOKIE.start);
+        assert!(matches!(DhcpPacket::try_from_dhcp(&no_cookie), Err(DhcpError::DhcpMessagePacketError)));
+        assert!(DhcpPacket::try_from(no_cookie.as_slice()).is_ok());
+    }
+
+    #[test]
+    fn test_write_to() {
+        use crate::fixtures::full_packet;
+
+        let mut buf = Vec::with_capacity(1024);
+        full_packet().write_to(&mut buf);
+        let bytes: Vec<u8> = full_packet().into();
+        assert_eq!(buf, bytes);
+
+        buf.clear();
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        DhcpPacket::try_from(from_bytes).unwrap().write_to(&mut buf);
+        let bytes: Vec<u8> = DhcpPacket::try_from(from_bytes).unwrap().into();
+        assert_eq!(buf, bytes);
+    }
+
+    #[test]
+    fn test_long_option_split() {
+        let name = AsciiString::from_ascii(vec![b'h'; 300]).unwrap();
+        let packet = packet_with_options(MacAddr6::nil(), vec![DhcpOption::HostName(name.clone())]);
+        let bytes: Vec<u8> = packet.into();
+        assert_eq!(&bytes[240..242], &[HOST_NAME, 255]);
+        assert_eq!(&bytes[497..499], &[HOST_NAME, 45]);
+
+        let parsed = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(parsed.options().option(HOST_NAME), Some(&DhcpOption::HostName(name)));
+    }
+
+    #[test]
+    fn test_client_hardware_padding() {
+        let macs: Vec<MacAddr> = vec![
+            MacAddr6::new(0, 1, 2, 3, 4, 5).into(),
+            MacAddr8::new(0, 1, 2, 3, 4, 5, 6, 7).into(),
+        ];
+
+        for mac in macs {
+            let packet = DhcpPacket::builder()
+                .client_hardware(mac)
+                .option(DhcpOption::MessageType(MessageType::Discover))
+                .build();
+            let bytes: Vec<u8> = packet.into();
+
+            assert_eq!(bytes[2] as usize, mac.as_bytes().len());
+            assert_eq!(&bytes[28..28 + mac.as_bytes().len()], mac.as_bytes());
+            assert!(bytes[28 + mac.as_bytes().len()..44].iter().all(|b| *b == 0));
+            assert_eq!(&bytes[236..240], &[0x63, 0x82, 0x53, 0x63]);
+            assert_eq!(&bytes[240..243], &[53, 1, 1]);
+
+            let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+            assert_eq!(packet.client_hardware(), &mac);
+        }
+    }
+
+    #[test]
+    fn test_hardware_address_types() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes.to_vec();
+        bytes[1] = 6;
+
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(packet.hardware_type(), &HardwareAddressType::IeeeIbm802);
+        let to_bytes: Vec<u8> = packet.into();
+        assert_eq!(to_bytes[1], 6);
+
+        bytes[1] = 8;
+        assert_eq!(DhcpPacket::try_from(bytes.as_slice()).unwrap().hardware_type(), &HardwareAddressType::Fddi);
+        bytes[1] = 0;
+        assert_eq!(DhcpPacket::try_from(bytes.as_slice()).unwrap().hardware_type(), &HardwareAddressType::Other(0));
+    }
+
+    #[test]
+    fn test_no_hardware_address() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes.to_vec();
+        bytes[1] = 0;
+        bytes[2] = 0;
+        bytes[28..44].fill(0);
+
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(packet.hardware_type(), &HardwareAddressType::Other(0));
+        assert_eq!(packet.client_hardware(), &MacAddr::from(MacAddr6::nil()));
+        let to_bytes: Vec<u8> = packet.into();
+        assert_eq!(to_bytes[..240], bytes[..240]);
+
+        bytes[1] = 1;
+        assert!(matches!(DhcpPacket::try_from(bytes.as_slice()), Err(DhcpError::HardwareAddressParseError)));
+    }
+
+    #[test]
+    fn test_flags_reserved_bits() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes.to_vec();
+
+        bytes[10..12].copy_from_slice(&[0x80, 0]);
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert!(packet.is_broadcast());
+        assert!(!packet.is_unicast());
+        let to_bytes: Vec<u8> = packet.into();
+        assert_eq!(&to_bytes[10..12], &[0x80, 0]);
+
+        bytes[10..12].copy_from_slice(&[0x81, 0x01]);
+        assert!(DhcpPacket::try_from(bytes.as_slice()).unwrap().is_broadcast());
+
+        bytes[10..12].copy_from_slice(&[0x7f, 0xff]);
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert!(packet.is_unicast());
+        assert!(!packet.is_broadcast());
+    }
+
+    #[test]
+    fn test_flags_broadcast_bit_15() {
+        let bytes: &[u8] = Flags::Broadcast.into();
+        assert_eq!(u16::from_be_bytes([bytes[0], bytes[1]]), 1 << 15);
+        let bytes: &[u8] = Flags::Unicast.into();
+        assert_eq!(u16::from_be_bytes([bytes[0], bytes[1]]), 0);
+
+        for reserved in [1u16, 0x00ff, 0x7fff] {
+            assert_eq!(Flags::try_from(&reserved.to_be_bytes()[..]).unwrap(), Flags::Unicast);
+            assert_eq!(Flags::try_from(&(reserved | 1 << 15).to_be_bytes()[..]).unwrap(), Flags::Broadcast);
+        }
+        assert!(matches!(Flags::try_from(&[0x80][..]), Err(DhcpError::InvalidFlag)));
+    }
+
+    #[test]
+    fn test_round_trip_equality() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let packet = DhcpPacket::try_from(from_bytes).unwrap();
+
+        let to_bytes: Vec<u8> = DhcpPacket::try_from(from_bytes).unwrap().into();
+        let reparsed = DhcpPacket::try_from(to_bytes.as_slice()).unwrap();
+        assert_eq!(packet, reparsed);
+
+        let mut changed = DhcpPacket::try_from(from_bytes).unwrap();
+        changed.options_mut().remove(crate::option::MESSAGE_TYPE);
+        assert_ne!(packet, changed);
+    }
+
+    #[test]
+    fn test_header_accessors() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut bytes = from_bytes.to_vec();
+        bytes[3] = 2;
+        bytes[8..10].copy_from_slice(&[0x01, 0x2c]);
+        bytes[10..12].copy_from_slice(&[0x80, 0]);
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(packet.transaction_id(), 0x0000_3d1d);
+        assert_eq!(packet.hops(), 2);
+        assert_eq!(packet.seconds(), 300);
+        assert_eq!(packet.flags(), &Flags::Broadcast);
+    }
+
+    #[test]
+    fn test_option_overload() {
+        let packet = packet_with_options(MacAddr6::nil(), vec![
+            DhcpOption::MessageType(MessageType::Discover),
+            DhcpOption::OptionOverload(Overload::Both),
+        ]);
+        let mut bytes: Vec<u8> = packet.into();
+        // message is split between file and sname, which are read in that order
+        let file = [HOST_NAME, 4, b'h', b'o', b's', b't', MESSAGE, 1, b'a', END];
+        let sname = [MESSAGE, 1, b'b', END];
+        bytes[FILENAME.start..FILENAME.start + file.len()].copy_from_slice(&file);
+        bytes[SERVER_HOSTNAME.start..SERVER_HOSTNAME.start + sname.len()].copy_from_slice(&sname);
+
+        let packet = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(packet.option(HOST_NAME), Some(&DhcpOption::HostName(AsciiString::from_ascii("host").unwrap())));
+        assert_eq!(packet.option(MESSAGE), Some(&DhcpOption::Message(AsciiString::from_ascii("ab").unwrap())));
+        assert_eq!(packet.message_type_value(), Some(MessageType::Discover));
+        assert!(packet.filename().is_empty());
+        assert!(packet.hostname().is_empty());
+        assert_eq!(packet.option(OPTION_OVERLOAD), None);
+        assert!(packet.raw_options().iter().all(|(tag, _)| *tag != OPTION_OVERLOAD));
+
+        // serialized back with all options in the options field and both fields zeroed
+        let reserialized: Vec<u8> = DhcpPacket::try_from(bytes.as_slice()).unwrap().into();
+        assert!(reserialized[SERVER_HOSTNAME.start..FILENAME.end].iter().all(|b| *b == 0));
+        assert_eq!(DhcpPacket::try_from(reserialized.as_slice()).unwrap(), packet);
+    }
+
+    #[test]
+    fn test_parse_prefix() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let (_, length) = DhcpOptions::from_bytes_with_len(&from_bytes[COOKIE.end..]).unwrap();
+        let packet_bytes = &from_bytes[..COOKIE.end + length];
+
+        let mut bytes = packet_bytes.to_vec();
+        bytes.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        let (packet, tail) = DhcpPacket::parse_prefix(&bytes).unwrap();
+        assert_eq!(tail, &[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(packet, DhcpPacket::try_from(packet_bytes).unwrap());
+
+        let (_, tail) = DhcpPacket::parse_prefix(packet_bytes).unwrap();
+        assert!(tail.is_empty());
+    }
+
+    #[test]
+    fn test_from_udp_datagram() {
+        let frame: &[u8] = include_bytes!("../client_request_frame.bin");
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let datagram = &frame[34..];
+
+        let packet = from_udp_datagram(datagram).unwrap();
+        assert_eq!(packet, DhcpPacket::try_from(from_bytes).unwrap());
+
+        let mut wrong_port = datagram.to_vec();
+        wrong_port[2..4].copy_from_slice(&53u16.to_be_bytes());
+        assert!(matches!(from_udp_datagram(&wrong_port), Err(DhcpError::NotDhcpPort)));
+
+        assert!(matches!(from_udp_datagram(&datagram[..4]), Err(DhcpError::FrameParseError(_))));
+        assert!(matches!(from_udp_datagram(&datagram[..100]), Err(DhcpError::FrameParseError(_))));
+    }
+
+    #[cfg(feature = "ethernet")]
+    #[test]
+    fn test_from_ethernet_frame() {
+        let frame: &[u8] = include_bytes!("../client_request_frame.bin");
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+
+        let packet = crate::dhcp::from_ethernet_frame(frame).unwrap();
+        assert_eq!(packet, DhcpPacket::try_from(from_bytes).unwrap());
+
+        // trailing ethernet padding is not part of the packet
+        let mut padded = frame.to_vec();
+        padded.extend_from_slice(&[0; 4]);
+        assert_eq!(crate::dhcp::from_ethernet_frame(&padded).unwrap(), packet);
+
+        let mut arp = frame.to_vec();
+        arp[12..14].copy_from_slice(&[0x08, 0x06]);
+        assert!(matches!(crate::dhcp::from_ethernet_frame(&arp), Err(DhcpError::FrameParseError(_))));
+
+        let mut tcp = frame.to_vec();
+        tcp[23] = 6;
+        assert!(matches!(crate::dhcp::from_ethernet_frame(&tcp), Err(DhcpError::FrameParseError(_))));
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let packet = DhcpPacket::try_from(from_bytes).unwrap();
+
+        let output = packet.pretty_print();
+        assert!(output.contains("xid: 0x00003d1d"));
+        assert!(output.contains("chaddr: 00:0B:82:01:FC:42"));
+        assert!(output.lines().any(|line| line.starts_with("  MessageType(")));
+    }
+
+    #[test]
+    fn test_tftp_servers() {
+        let from_bytes: &[u8] = include_bytes!("../client_request.bin");
+        let mut packet = DhcpPacket::try_from(from_bytes).unwrap();
+        packet.server = Ipv4Addr::UNSPECIFIED;
+        assert!(packet.tftp_servers().is_empty());
+
+        packet.server = Ipv4Addr::new(10, 0, 0, 1);
+        assert_eq!(packet.tftp_servers(), vec!["10.0.0.1"]);
+
+        packet.options_mut().upsert(DhcpOption::TftpServerAddress(vec![Ipv4Addr::new(10, 0, 0, 2), Ipv4Addr::new(10, 0, 0, 3)]));
+        assert_eq!(packet.tftp_servers(), vec!["10.0.0.2", "10.0.0.3"]);
+
+        packet.options_mut().upsert(DhcpOption::TftpServer(AsciiString::from_ascii("tftp.local").unwrap()));
+        assert_eq!(packet.tftp_servers(), vec!["tftp.local"]);
+    }
 }
@@ -1,5 +1,6 @@
 use std::fmt::{Display, Formatter};
 use std::error::Error;
+use std::io;
 
 #[cfg(feature = "with_serde")]
 use serde::{Serialize, Deserialize};
@@ -28,12 +29,101 @@ pub enum DhcpError {
     ConversionError(u8),
     OptionNotExist(u8),
     InvalidPacketLength(u8),
+    HopLimitExceeded,
+    ServerHostnameParseError,
+    FilenameParseError,
+    ResponseOperationMismatch,
+    ResponseTransactionIdMismatch,
+    ResponseClientHardwareMismatch,
+    ResponseGatewayMismatch,
+    ResponseServerIdentifierMissing,
+    UnexpectedEndOfOptions,
+    #[cfg_attr(feature = "with_serde", serde(skip_deserializing))]
+    PacketValidation(&'static str),
+    #[cfg_attr(feature = "with_serde", serde(serialize_with = "serialize_error_kind", skip_deserializing))]
+    SocketError(io::ErrorKind),
+    NotDhcpPort,
+    #[cfg_attr(feature = "with_serde", serde(skip_deserializing))]
+    FrameParseError(&'static str),
 }
 
 impl Display for DhcpError {
     fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
-        write!(f, "{}", self)
+        match self {
+            DhcpError::MessageOperationInvalid => write!(f, "invalid message operation"),
+            DhcpError::HardwareAddressTypeParseError => write!(f, "invalid hardware address type"),
+            DhcpError::HardwareAddressParseError => write!(f, "invalid client hardware address"),
+            DhcpError::TransactionIdParseError => write!(f, "invalid transaction id"),
+            DhcpError::SecondsParseError => write!(f, "invalid seconds"),
+            DhcpError::ClientAddressParseError => write!(f, "invalid client address"),
+            DhcpError::YourAddressParseError => write!(f, "invalid your address"),
+            DhcpError::ServerAddressParseError => write!(f, "invalid server address"),
+            DhcpError::GatewayAddressParseError => write!(f, "invalid gateway address"),
+            DhcpError::CookieParseError => write!(f, "invalid magic cookie"),
+            DhcpError::InvalidFlag => write!(f, "invalid flags"),
+            DhcpError::OptionParseError(tag) => write!(f, "failed to parse option {}", tag),
+            DhcpError::OptionInvalidValueError(tag) => write!(f, "invalid value for option {}", tag),
+            DhcpError::DhcpMessagePacketError => write!(f, "invalid message type for this packet"),
+            DhcpError::ConversionError(tag) => write!(f, "option {} cannot be converted", tag),
+            DhcpError::OptionNotExist(tag) => write!(f, "option {} does not exist", tag),
+            DhcpError::InvalidPacketLength(length) => write!(f, "invalid packet length {}", length),
+            DhcpError::HopLimitExceeded => write!(f, "hop limit exceeded"),
+            DhcpError::ServerHostnameParseError => write!(f, "invalid server hostname"),
+            DhcpError::FilenameParseError => write!(f, "invalid boot filename"),
+            DhcpError::ResponseOperationMismatch => write!(f, "response is not a boot reply"),
+            DhcpError::ResponseTransactionIdMismatch => write!(f, "response transaction id does not match the request"),
+            DhcpError::ResponseClientHardwareMismatch => write!(f, "response client hardware address does not match the request"),
+            DhcpError::ResponseGatewayMismatch => write!(f, "response gateway address does not match the request"),
+            DhcpError::ResponseServerIdentifierMissing => write!(f, "response has no server identifier"),
+            DhcpError::UnexpectedEndOfOptions => write!(f, "options ended without end option"),
+            DhcpError::PacketValidation(reason) => write!(f, "invalid packet: {}", reason),
+            DhcpError::SocketError(kind) => write!(f, "socket error: {}", kind),
+            DhcpError::NotDhcpPort => write!(f, "udp destination port is not a dhcp port"),
+            DhcpError::FrameParseError(reason) => write!(f, "invalid frame: {}", reason),
+        }
     }
 }
 
 impl Error for DhcpError { fn source(&self) -> Option<&(dyn Error + 'static)> { None } }
+
+impl From<DhcpError> for io::Error {
+    fn from(error: DhcpError) -> Self {
+        match error {
+            DhcpError::SocketError(kind) => io::Error::new(kind, error),
+            _ => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+#[cfg(feature = "with_serde")]
+fn serialize_error_kind<S: serde::Serializer>(kind: &io::ErrorKind, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_io_error() {
+        let error: io::Error = DhcpError::OptionParseError(6).into();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(error.get_ref().and_then(|e| e.downcast_ref::<DhcpError>()), Some(DhcpError::OptionParseError(6))));
+
+        let error: io::Error = DhcpError::SocketError(io::ErrorKind::AddrInUse).into();
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+    }
+
+    #[test]
+    fn test_display() {
+        let parse = DhcpError::OptionParseError(6).to_string();
+        let invalid = DhcpError::OptionInvalidValueError(6).to_string();
+        let cookie = DhcpError::CookieParseError.to_string();
+
+        assert!(!parse.is_empty() && !invalid.is_empty() && !cookie.is_empty());
+        assert!(parse.contains('6'));
+        assert_ne!(parse, invalid);
+        assert_ne!(parse, DhcpError::OptionParseError(7).to_string());
+        assert_ne!(invalid, cookie);
+    }
+}
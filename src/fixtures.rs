@@ -0,0 +1,50 @@
+use std::convert::TryFrom;
+use std::net::Ipv4Addr;
+use ascii::AsciiString;
+use macaddr::MacAddr6;
+use crate::dhcp::{DhcpPacket, MessageOperation, HardwareAddressType, Flags};
+use crate::option::{DhcpOption, MessageType, NetBiosNodeType, RelayAgentInformationSubOption};
+
+/// Packet with a representative option of each value kind
+pub(crate) fn full_packet() -> DhcpPacket {
+    DhcpPacket::new(
+        MessageOperation::BootReply,
+        HardwareAddressType::Ethernet,
+        1,
+        0x1234_5678,
+        3,
+        Flags::Broadcast,
+        Ipv4Addr::UNSPECIFIED,
+        Ipv4Addr::new(192, 168, 1, 10),
+        Ipv4Addr::new(192, 168, 1, 1),
+        Ipv4Addr::new(192, 168, 2, 1),
+        MacAddr6::new(0, 1, 2, 3, 4, 5),
+        AsciiString::from_ascii("server").unwrap(),
+        AsciiString::from_ascii("boot.img").unwrap(),
+        vec![
+            DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0)),
+            DhcpOption::Router(vec![Ipv4Addr::new(192, 168, 1, 1)]),
+            DhcpOption::DomainNameServer(vec![Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(8, 8, 8, 8)]),
+            DhcpOption::HostName(AsciiString::from_ascii("client").unwrap()),
+            DhcpOption::IpForwarding(false),
+            DhcpOption::NetBiosOverTcpIpNodeType(NetBiosNodeType::H),
+            DhcpOption::IpAddressLeaseTime(7200),
+            DhcpOption::MessageType(MessageType::Offer),
+            DhcpOption::ServerIdentifier(Ipv4Addr::new(192, 168, 1, 1)),
+            DhcpOption::RelayAgentInformation(vec![
+                RelayAgentInformationSubOption::AgentCircuit(vec![1, 2]),
+                RelayAgentInformationSubOption::AgentRemote(vec![3, 4, 5]),
+            ]),
+            DhcpOption::End,
+        ],
+    )
+}
+
+#[test]
+fn test_full_packet_round_trip() {
+    let bytes: Vec<u8> = full_packet().into();
+    let reparsed = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+
+    assert_eq!(reparsed.options().options(), full_packet().options().options());
+    assert_eq!(Vec::<u8>::from(reparsed), bytes);
+}
@@ -13,5 +13,13 @@ pub mod convert;
 #[cfg(feature = "messaging")]
 pub mod messaging;
 
+/// UDP socket helper
+#[cfg(feature = "messaging")]
+pub mod socket;
+
+/// Shared test packets
+#[cfg(test)]
+mod fixtures;
+
 pub use crate::dhcp::*;
 
@@ -2,8 +2,8 @@ use ascii::AsciiString;
 use std::convert::{TryFrom, TryInto};
 use std::net::Ipv4Addr;
 use crate::DhcpPacket;
-use crate::dhcp::{Flags, MessageOperation, HardwareAddressType, MacAddress};
-use crate::error::DhcpError;
+use crate::dhcp::{Flags, MessageOperation, HardwareAddressType, MacAddress, HARDWARE_ADDRESS_TYPE_ETHERNET};
+use crate::error::{DhcpError, DhcpResult};
 use crate::option::{DhcpOptions, DhcpOption, MessageType, ClientIdentifier,
                     MESSAGE_TYPE, REQUESTED_IP_ADDRESS, PARAMETER_REQUEST_LIST, CLIENT_IDENTIFIER,
                     MAXIMUM_DHCP_MESSAGE_SIZE, SERVER_IDENTIFIER, IP_ADDRESS_LEASE_TIME,
@@ -46,6 +46,14 @@ packet!(DhcpAckPacket);
 
 packet!(DhcpNakPacket);
 
+packet!(DhcpLeaseQueryPacket);
+
+packet!(DhcpLeaseUnassignedPacket);
+
+packet!(DhcpLeaseUnknownPacket);
+
+packet!(DhcpLeaseActivePacket);
+
 /// Represents all dhcp message types with possible conversations.
 ///
 /// Reflects the Client/Server communication.
@@ -58,6 +66,10 @@ pub enum DhcpMessaging {
     Decline(DhcpDeclinePacket),
     Ack(DhcpAckPacket),
     Nak(DhcpNakPacket),
+    LeaseQuery(DhcpLeaseQueryPacket),
+    LeaseUnassigned(DhcpLeaseUnassignedPacket),
+    LeaseUnknown(DhcpLeaseUnknownPacket),
+    LeaseActive(DhcpLeaseActivePacket),
 }
 
 // todo: https://datatracker.ietf.org/doc/html/rfc2131#section-4.3.6 ?
@@ -73,7 +85,91 @@ impl DhcpMessaging {
             DhcpMessaging::Decline(p) => &p.packet,
             DhcpMessaging::Ack(p) => &p.packet,
             DhcpMessaging::Nak(p) => &p.packet,
+            DhcpMessaging::LeaseQuery(p) => &p.packet,
+            DhcpMessaging::LeaseUnassigned(p) => &p.packet,
+            DhcpMessaging::LeaseUnknown(p) => &p.packet,
+            DhcpMessaging::LeaseActive(p) => &p.packet,
+        }
+    }
+
+    /// Checks that this packet is a well-formed reply to `request`.
+    ///
+    /// Operation, transaction id, client hardware address, gateway and the server identifier option
+    /// are checked in that order and the first mismatch is returned as error.
+    pub fn is_valid_response_to(&self, request: &DhcpPacket) -> Result<(), DhcpError> {
+        let packet = self.packet();
+
+        if !matches!(packet.operation, MessageOperation::BootReply) {
+            return Err(DhcpError::ResponseOperationMismatch);
+        }
+        if packet.transaction_id != request.transaction_id {
+            return Err(DhcpError::ResponseTransactionIdMismatch);
+        }
+        if packet.client_hardware != request.client_hardware {
+            return Err(DhcpError::ResponseClientHardwareMismatch);
+        }
+        if packet.gateway != request.gateway {
+            return Err(DhcpError::ResponseGatewayMismatch);
         }
+        if packet.server_identifier_ip().is_none() {
+            return Err(DhcpError::ResponseServerIdentifierMissing);
+        }
+
+        Ok(())
+    }
+
+    /// Creates a reply to a client request.
+    ///
+    /// Transaction id, hardware type and address, gateway and flags are taken from the request.
+    /// `message_type` must answer the request, e.g. an `Offer` for a `Discover` or an `Ack`/`Nak`
+    /// for a `Request`, otherwise [`DhcpError::DhcpMessagePacketError`] is returned.
+    pub fn respond<O>(request: &DhcpPacket,
+                      message_type: MessageType,
+                      your_ip_address: Ipv4Addr,
+                      server_ip_address: Ipv4Addr,
+                      options: O,
+    ) -> DhcpResult<DhcpMessaging>
+        where
+            O: Into<DhcpOptions>,
+    {
+        let request_type = match request.message_type() {
+            Some(DhcpOption::MessageType(t)) => t,
+            _ => return Err(DhcpError::DhcpMessagePacketError),
+        };
+
+        let client_ip_address = match (request_type, &message_type) {
+            (MessageType::Discover, MessageType::Offer) => Ipv4Addr::UNSPECIFIED,
+            (MessageType::Request, MessageType::Ack) => request.client,
+            (MessageType::Inform, MessageType::Ack) => request.client,
+            (MessageType::Request, MessageType::Nak) => Ipv4Addr::UNSPECIFIED,
+            _ => return Err(DhcpError::DhcpMessagePacketError),
+        };
+
+        let your_ip_address = match message_type {
+            MessageType::Nak => Ipv4Addr::UNSPECIFIED,
+            _ => your_ip_address,
+        };
+
+        let mut options = options.into();
+        options.upsert(DhcpOption::MessageType(message_type));
+        options.upsert(DhcpOption::ServerIdentifier(server_ip_address));
+
+        DhcpPacket::new(
+            MessageOperation::BootReply,
+            request.hardware_type.clone(),
+            0,
+            request.transaction_id,
+            0,
+            request.flags.clone(),
+            client_ip_address,
+            your_ip_address,
+            Ipv4Addr::UNSPECIFIED,
+            request.gateway,
+            request.client_hardware.clone(),
+            AsciiString::default(),
+            AsciiString::default(),
+            options,
+        ).try_into()
     }
 
     /// Creates a decline packet.
@@ -82,6 +178,17 @@ impl DhcpMessaging {
     ) -> DhcpDeclinePacket
         where
             C: Into<MacAddress>,
+    {
+        Self::decline_with_xid(rand::random(), client_mac_address)
+    }
+
+    /// Creates a decline packet with a fixed transaction id.
+    pub fn decline_with_xid<C>(
+        transaction_id: u32,
+        client_mac_address: C,
+    ) -> DhcpDeclinePacket
+        where
+            C: Into<MacAddress>,
     {
         let options: DhcpOptions = vec![
             DhcpOption::MessageType(MessageType::Decline)
@@ -92,7 +199,7 @@ impl DhcpMessaging {
                 MessageOperation::BootRequest,
                 HardwareAddressType::Ethernet,
                 0,
-                rand::random(),
+                transaction_id,
                 0,
                 Flags::Unicast,
                 Ipv4Addr::UNSPECIFIED,
@@ -108,23 +215,99 @@ impl DhcpMessaging {
     }
 
     /// Creates a release packet.
+    ///
+    /// Without `client_identifier` the hardware address is used as identifier.
     pub fn release<C>(
         client_mac_address: C,
         client_ip_address: Ipv4Addr,
-    ) -> DhcpDeclinePacket
+        server_ip_address: Ipv4Addr,
+        client_identifier: Option<ClientIdentifier>,
+    ) -> DhcpReleasePacket
+        where
+            C: Into<MacAddress>,
+    {
+        Self::release_with_xid(rand::random(), client_mac_address, client_ip_address, server_ip_address,
+                               client_identifier)
+    }
+
+    /// Creates a release packet with a fixed transaction id.
+    pub fn release_with_xid<C>(
+        transaction_id: u32,
+        client_mac_address: C,
+        client_ip_address: Ipv4Addr,
+        server_ip_address: Ipv4Addr,
+        client_identifier: Option<ClientIdentifier>,
+    ) -> DhcpReleasePacket
         where
             C: Into<MacAddress>,
     {
+        let client_mac_address = client_mac_address.into();
+        let client_identifier = client_identifier.unwrap_or_else(|| {
+            ClientIdentifier::new(HARDWARE_ADDRESS_TYPE_ETHERNET, client_mac_address.as_bytes().to_vec())
+        });
+
         let options: DhcpOptions = vec![
-            DhcpOption::MessageType(MessageType::Decline)
+            DhcpOption::MessageType(MessageType::Release),
+            DhcpOption::ServerIdentifier(server_ip_address),
+            DhcpOption::ClientIdentifier(client_identifier),
         ].into();
 
-        DhcpDeclinePacket {
+        DhcpReleasePacket {
+            packet: DhcpPacket::new(
+                MessageOperation::BootRequest,
+                HardwareAddressType::Ethernet,
+                0,
+                transaction_id,
+                0,
+                Flags::Unicast,
+                client_ip_address,
+                Ipv4Addr::UNSPECIFIED,
+                Ipv4Addr::UNSPECIFIED,
+                Ipv4Addr::UNSPECIFIED,
+                client_mac_address,
+                AsciiString::default(),
+                AsciiString::default(),
+                options,
+            )
+        }
+    }
+
+    /// Creates a request of a client in RENEWING state (rfc2131 4.3.6)
+    ///
+    /// `ciaddr` is set to the bound address, no requested ip or server identifier is sent.
+    /// Send it unicast to the server which granted the lease.
+    pub fn renew<C>(client_mac_address: C,
+                    client_ip_address: Ipv4Addr,
+                    client_identifier: Option<ClientIdentifier>,
+                    parameter_requested_list: Option<Vec<u8>>,
+    ) -> DhcpRequestPacket
+        where
+            C: Into<MacAddress>,
+    {
+        Self::renew_with_xid(rand::random(), client_mac_address, client_ip_address, client_identifier,
+                             parameter_requested_list)
+    }
+
+    /// Creates a renewing request with a fixed transaction id.
+    pub fn renew_with_xid<C>(transaction_id: u32,
+                             client_mac_address: C,
+                             client_ip_address: Ipv4Addr,
+                             client_identifier: Option<ClientIdentifier>,
+                             parameter_requested_list: Option<Vec<u8>>,
+    ) -> DhcpRequestPacket
+        where
+            C: Into<MacAddress>,
+    {
+        let mut options = DhcpOptions::from(vec![DhcpOption::MessageType(MessageType::Request)]);
+        options.upsert_option(client_identifier.map(DhcpOption::ClientIdentifier));
+        options.upsert_option(parameter_requested_list.map(DhcpOption::ParameterRequestList));
+
+        DhcpRequestPacket {
             packet: DhcpPacket::new(
                 MessageOperation::BootRequest,
                 HardwareAddressType::Ethernet,
                 0,
-                rand::random(),
+                transaction_id,
                 0,
                 Flags::Unicast,
                 client_ip_address,
@@ -139,8 +322,37 @@ impl DhcpMessaging {
         }
     }
 
+    /// Creates a request of a client in REBINDING state (rfc2131 4.3.6)
+    ///
+    /// Same packet as [`DhcpMessaging::renew`], but broadcast it since any server may extend the lease.
+    pub fn rebind<C>(client_mac_address: C,
+                     client_ip_address: Ipv4Addr,
+                     client_identifier: Option<ClientIdentifier>,
+                     parameter_requested_list: Option<Vec<u8>>,
+    ) -> DhcpRequestPacket
+        where
+            C: Into<MacAddress>,
+    {
+        Self::rebind_with_xid(rand::random(), client_mac_address, client_ip_address, client_identifier,
+                              parameter_requested_list)
+    }
+
+    /// Creates a rebinding request with a fixed transaction id.
+    pub fn rebind_with_xid<C>(transaction_id: u32,
+                              client_mac_address: C,
+                              client_ip_address: Ipv4Addr,
+                              client_identifier: Option<ClientIdentifier>,
+                              parameter_requested_list: Option<Vec<u8>>,
+    ) -> DhcpRequestPacket
+        where
+            C: Into<MacAddress>,
+    {
+        Self::renew_with_xid(transaction_id, client_mac_address, client_ip_address, client_identifier,
+                             parameter_requested_list)
+    }
+
     /// Creates a inform packet.
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
+    #[allow(clippy::too_many_arguments)]
     pub fn inform<C, O>(client_mac_address: C,
                         client_ip_address: Ipv4Addr,
                         client_identifier: Option<ClientIdentifier>,
@@ -153,6 +365,33 @@ impl DhcpMessaging {
         where
             C: Into<MacAddress>,
             O: Into<DhcpOptions>,
+    {
+        Self::inform_with_xid(rand::random(),
+                              client_mac_address,
+                              client_ip_address,
+                              client_identifier,
+                              vendor_class_identifier,
+                              parameter_requested_list,
+                              maximum_accepted_size,
+                              broadcast,
+                              additional_options)
+    }
+
+    /// Creates a inform packet with a fixed transaction id.
+    #[allow(clippy::too_many_arguments)]
+    pub fn inform_with_xid<C, O>(transaction_id: u32,
+                                 client_mac_address: C,
+                                 client_ip_address: Ipv4Addr,
+                                 client_identifier: Option<ClientIdentifier>,
+                                 vendor_class_identifier: Option<Vec<u8>>,
+                                 parameter_requested_list: Option<Vec<u8>>,
+                                 maximum_accepted_size: Option<u16>,
+                                 broadcast: bool,
+                                 additional_options: O,
+    ) -> DhcpInformPacket
+        where
+            C: Into<MacAddress>,
+            O: Into<DhcpOptions>,
     {
         let mut options = additional_options.into();
         options.upsert(DhcpOption::MessageType(MessageType::Inform));
@@ -172,7 +411,7 @@ impl DhcpMessaging {
                 MessageOperation::BootRequest,
                 HardwareAddressType::Ethernet,
                 0,
-                rand::random(),
+                transaction_id,
                 0,
                 flag,
                 client_ip_address,
@@ -187,7 +426,8 @@ impl DhcpMessaging {
         }
     }
 
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
+    /// Creates a discover packet.
+    #[allow(clippy::too_many_arguments)]
     pub fn discover<C, O>(client_mac_address: C,
                           requested_ip_address: Option<Ipv4Addr>,
                           lease_time: Option<u32>,
@@ -200,6 +440,33 @@ impl DhcpMessaging {
         where
             C: Into<MacAddress>,
             O: Into<DhcpOptions>,
+    {
+        Self::discover_with_xid(rand::random(),
+                                client_mac_address,
+                                requested_ip_address,
+                                lease_time,
+                                client_identifier,
+                                vendor_class_identifier,
+                                parameter_requested_list,
+                                maximum_accepted_size,
+                                additional_options)
+    }
+
+    /// Creates a discover packet with a fixed transaction id.
+    #[allow(clippy::too_many_arguments)]
+    pub fn discover_with_xid<C, O>(transaction_id: u32,
+                                   client_mac_address: C,
+                                   requested_ip_address: Option<Ipv4Addr>,
+                                   lease_time: Option<u32>,
+                                   client_identifier: Option<ClientIdentifier>,
+                                   vendor_class_identifier: Option<Vec<u8>>,
+                                   parameter_requested_list: Option<Vec<u8>>,
+                                   maximum_accepted_size: Option<u16>,
+                                   additional_options: O,
+    ) -> DhcpDiscoverPacket
+        where
+            C: Into<MacAddress>,
+            O: Into<DhcpOptions>,
     {
         let mut options = additional_options.into();
         options.upsert(DhcpOption::MessageType(MessageType::Discover));
@@ -218,7 +485,7 @@ impl DhcpMessaging {
                 MessageOperation::BootRequest,
                 HardwareAddressType::Ethernet,
                 0,
-                rand::random(),
+                transaction_id,
                 0,
                 Flags::Broadcast,
                 Ipv4Addr::UNSPECIFIED,
@@ -245,6 +512,10 @@ impl From<DhcpMessaging> for Vec<u8> {
             DhcpMessaging::Decline(p) => { p.packet.into() }
             DhcpMessaging::Ack(p) => { p.packet.into() }
             DhcpMessaging::Nak(p) => { p.packet.into() }
+            DhcpMessaging::LeaseQuery(p) => { p.packet.into() }
+            DhcpMessaging::LeaseUnassigned(p) => { p.packet.into() }
+            DhcpMessaging::LeaseUnknown(p) => { p.packet.into() }
+            DhcpMessaging::LeaseActive(p) => { p.packet.into() }
         }
     }
 }
@@ -287,6 +558,18 @@ impl TryFrom<DhcpPacket> for DhcpMessaging {
             Some(DhcpOption::MessageType(MessageType::Nak)) => {
                 DhcpMessaging::Nak(DhcpNakPacket { packet })
             }
+            Some(DhcpOption::MessageType(MessageType::LeaseQuery)) => {
+                DhcpMessaging::LeaseQuery(DhcpLeaseQueryPacket { packet })
+            }
+            Some(DhcpOption::MessageType(MessageType::LeaseUnassigned)) => {
+                DhcpMessaging::LeaseUnassigned(DhcpLeaseUnassignedPacket { packet })
+            }
+            Some(DhcpOption::MessageType(MessageType::LeaseUnknown)) => {
+                DhcpMessaging::LeaseUnknown(DhcpLeaseUnknownPacket { packet })
+            }
+            Some(DhcpOption::MessageType(MessageType::LeaseActive)) => {
+                DhcpMessaging::LeaseActive(DhcpLeaseActivePacket { packet })
+            }
             _ => {
                 return Err(DhcpError::DhcpMessagePacketError);
             }
@@ -294,10 +577,16 @@ impl TryFrom<DhcpPacket> for DhcpMessaging {
     }
 }
 
+fn relay_target(packet: &DhcpPacket) -> Option<Ipv4Addr> {
+    Some(packet.gateway).filter(|gateway| !gateway.is_unspecified())
+}
+
 packet!(DhcpDiscoverPacket);
 
 impl DhcpDiscoverPacket {
     /// Converts a discover packet into an offer packet
+    ///
+    /// The relay gateway (giaddr) is kept, see [`DhcpOfferPacket::relay_target`].
     pub fn into_offer<I, O>(mut self,
                             lease: u32,
                             client_ip_address: I,
@@ -319,7 +608,7 @@ impl DhcpDiscoverPacket {
         self.packet.client = Ipv4Addr::UNSPECIFIED;
         self.packet.your = client_ip_address.into();
         self.packet.server = server_ip;
-        self.packet.filename = filename.map(Into::into).unwrap_or_default();
+        self.packet.filename = filename.unwrap_or_default();
 
         self.packet.options_mut().merge(additional_options.into());
         self.packet.options_mut().upsert_option(message.map(DhcpOption::Message));
@@ -339,7 +628,7 @@ packet!(DhcpInformPacket);
 
 impl DhcpInformPacket {
     /// Converts an inform packet into an ack packet
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
+    #[allow(clippy::too_many_arguments)]
     pub fn into_ack<O>(mut self,
                        client_ip_address: Ipv4Addr,
                        server_ip_address: Ipv4Addr,
@@ -380,7 +669,9 @@ packet!(DhcpRequestPacket);
 
 impl DhcpRequestPacket {
     /// Converts an request packet into an ack packet
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
+    ///
+    /// The relay gateway (giaddr) is kept, see [`DhcpAckPacket::relay_target`].
+    #[allow(clippy::too_many_arguments)]
     pub fn into_ack<O>(mut self,
                        lease: u32,
                        client_ip_address: Ipv4Addr,
@@ -449,11 +740,64 @@ impl DhcpRequestPacket {
     }
 }
 
+impl DhcpAckPacket {
+    /// Relay agent the ack must be unicast to, `None` if the client is on the local network
+    pub fn relay_target(&self) -> Option<Ipv4Addr> {
+        relay_target(&self.packet)
+    }
+
+    /// Converts the ack of a bound lease into a release packet
+    ///
+    /// Transaction id, client hardware address and the leased address are taken from the ack.
+    /// Without `client_identifier` the hardware address is used as identifier.
+    pub fn into_release(self,
+                        server_ip_address: Ipv4Addr,
+                        client_identifier: Option<ClientIdentifier>,
+    ) -> DhcpReleasePacket {
+        let client_ip_address = self.leased_address();
+        DhcpMessaging::release_with_xid(self.packet.transaction_id,
+                                        self.packet.client_hardware,
+                                        client_ip_address,
+                                        server_ip_address,
+                                        client_identifier)
+    }
+
+    /// Converts the ack into a decline packet for `requested_ip_address`
+    ///
+    /// Transaction id, client hardware address and the server identifier are taken from the ack.
+    pub fn into_decline(self,
+                        requested_ip_address: Ipv4Addr,
+                        message: Option<AsciiString>,
+    ) -> DhcpDeclinePacket {
+        let server_identifier = self.packet.server_identifier_ip();
+        let mut decline = DhcpMessaging::decline_with_xid(self.packet.transaction_id, self.packet.client_hardware);
+
+        decline.packet.options_mut().upsert(DhcpOption::RequestedIpAddress(requested_ip_address));
+        decline.packet.options_mut().upsert_option(server_identifier.map(DhcpOption::ServerIdentifier));
+        decline.packet.options_mut().upsert_option(message.map(DhcpOption::Message));
+        decline
+    }
+
+    /// Address assigned by the ack, `ciaddr` for the ack to an inform
+    fn leased_address(&self) -> Ipv4Addr {
+        if self.packet.your.is_unspecified() {
+            self.packet.client
+        } else {
+            self.packet.your
+        }
+    }
+}
+
 packet!(DhcpOfferPacket);
 
 impl DhcpOfferPacket {
+    /// Relay agent the offer must be unicast to, `None` if the client is on the local network
+    pub fn relay_target(&self) -> Option<Ipv4Addr> {
+        relay_target(&self.packet)
+    }
+
     /// Converts an offer packet into an request packet
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
+    #[allow(clippy::too_many_arguments)]
     pub fn into_request<C, O>(mut self,
                               client_hardware_address: C,
                               seconds: u16,
@@ -544,4 +888,236 @@ fn test() {
         None,
         None,
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_discover_with_xid() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+
+    let first = DhcpMessaging::discover_with_xid(0x1234, client_mac, None, None, None, None, None, None, None);
+    let second = DhcpMessaging::discover_with_xid(0x1234, client_mac, None, None, None, None, None, None, None);
+
+    assert_eq!(first.packet().transaction_id, 0x1234);
+    assert_eq!(first.packet().transaction_id, second.packet().transaction_id);
+}
+
+#[test]
+fn test_replies_without_maximum_message_size() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let client_ip = Ipv4Addr::new(1, 2, 3, 4);
+    let server_ip = Ipv4Addr::new(5, 6, 7, 8);
+
+    let discover = DhcpMessaging::discover(client_mac, None, None, None, None, None, Some(1500), None);
+    assert_eq!(discover.packet().client_max_message_size(), Some(1500));
+
+    let offer = discover.into_offer(7200, client_ip, server_ip, None, None, None);
+    assert!(offer.packet().option(MAXIMUM_DHCP_MESSAGE_SIZE).is_none());
+
+    let request = offer.into_request(client_mac, 0, None, false, Some(client_ip), None, None, None,
+                                     Some(server_ip), None, Some(1500), None);
+    assert_eq!(request.packet().client_max_message_size(), Some(1500));
+
+    let ack = request.into_ack(7200, client_ip, server_ip, None, None, None, None, None);
+    assert!(ack.packet().option(MAXIMUM_DHCP_MESSAGE_SIZE).is_none());
+
+    let inform = DhcpMessaging::inform(client_mac, client_ip, None, None, None, Some(1500), false, None);
+    let ack = inform.into_ack(client_ip, server_ip, None, None, None, None, None);
+    assert!(ack.packet().option(MAXIMUM_DHCP_MESSAGE_SIZE).is_none());
+}
+
+#[test]
+fn test_respond() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let client_ip = Ipv4Addr::new(1, 2, 3, 4);
+    let server_ip = Ipv4Addr::new(5, 6, 7, 8);
+
+    let bytes: Vec<u8> = DhcpMessaging::discover_with_xid(42, client_mac, None, None, None, None, None, None,
+                                                          vec![DhcpOption::End]).packet.into();
+    let discover = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+
+    let offer = DhcpMessaging::respond(&discover, MessageType::Offer, client_ip, server_ip,
+                                       vec![DhcpOption::IpAddressLeaseTime(7200)]).unwrap();
+    let packet = match &offer {
+        DhcpMessaging::Offer(p) => &p.packet,
+        _ => panic!("expected offer"),
+    };
+    assert_eq!(packet.transaction_id, 42);
+    assert_eq!(packet.hardware_type, HardwareAddressType::Ethernet);
+    assert_eq!(packet.client_hardware, client_mac.into());
+    assert_eq!(packet.your, client_ip);
+    assert_eq!(packet.flags, Flags::Broadcast);
+    assert_eq!(packet.option(SERVER_IDENTIFIER), Some(&DhcpOption::ServerIdentifier(server_ip)));
+    assert_eq!(packet.option(IP_ADDRESS_LEASE_TIME), Some(&DhcpOption::IpAddressLeaseTime(7200)));
+
+    assert!(DhcpMessaging::respond(&discover, MessageType::Ack, client_ip, server_ip, None).is_err());
+
+    let mut ieee802 = bytes.clone();
+    ieee802[1] = HardwareAddressType::IeeeIbm802.into();
+    let discover = DhcpPacket::try_from(ieee802.as_slice()).unwrap();
+    match DhcpMessaging::respond(&discover, MessageType::Offer, client_ip, server_ip, None).unwrap() {
+        DhcpMessaging::Offer(p) => assert_eq!(p.packet.hardware_type, HardwareAddressType::IeeeIbm802),
+        _ => panic!("expected offer"),
+    }
+
+    let request = match offer {
+        DhcpMessaging::Offer(p) => p.into_request(client_mac, 0, None, false, Some(client_ip), None, None, None,
+                                                  Some(server_ip), None, None, vec![DhcpOption::End]),
+        _ => unreachable!(),
+    };
+    let bytes: Vec<u8> = request.packet.into();
+    let request = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+
+    let ack = DhcpMessaging::respond(&request, MessageType::Ack, client_ip, server_ip, None).unwrap();
+    match ack {
+        DhcpMessaging::Ack(p) => {
+            assert_eq!(p.packet.transaction_id, 42);
+            assert_eq!(p.packet.your, client_ip);
+        }
+        _ => panic!("expected ack"),
+    }
+}
+
+#[test]
+fn test_is_valid_response_to() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let client_ip = Ipv4Addr::new(1, 2, 3, 4);
+    let server_ip = Ipv4Addr::new(5, 6, 7, 8);
+
+    let discover = DhcpMessaging::discover_with_xid(42, client_mac, None, None, None, None, None, None,
+                                                    vec![DhcpOption::End]);
+    let bytes: Vec<u8> = DhcpPacket::from(discover).into();
+    let request = DhcpPacket::try_from(bytes.as_slice()).unwrap();
+    let discover = DhcpDiscoverPacket::from(DhcpPacket::try_from(bytes.as_slice()).unwrap());
+
+    let offer: DhcpMessaging = DhcpMessaging::Offer(discover.into_offer(7200, client_ip, server_ip, None, None, None));
+    assert!(offer.is_valid_response_to(&request).is_ok());
+
+    let mut packet: DhcpPacket = match offer {
+        DhcpMessaging::Offer(p) => p.into(),
+        _ => unreachable!(),
+    };
+    packet.transaction_id = 43;
+    let offer = DhcpMessaging::Offer(packet.into());
+    assert!(matches!(offer.is_valid_response_to(&request), Err(DhcpError::ResponseTransactionIdMismatch)));
+}
+
+#[test]
+fn test_release() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let client_ip = Ipv4Addr::new(1, 2, 3, 4);
+    let server_ip = Ipv4Addr::new(5, 6, 7, 8);
+
+    let release = DhcpMessaging::release(client_mac, client_ip, server_ip, None);
+    let packet = release.packet();
+
+    assert_eq!(packet.message_type(), Some(&DhcpOption::MessageType(MessageType::Release)));
+    assert_eq!(packet.server_identifier_ip(), Some(server_ip));
+    assert_eq!(packet.option(CLIENT_IDENTIFIER),
+               Some(&DhcpOption::ClientIdentifier(ClientIdentifier::new(1, vec![0, 1, 2, 3, 4, 5]))));
+    assert_eq!(packet.client, client_ip);
+}
+
+#[test]
+fn test_lease_query_round_trip() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let types = vec![MessageType::LeaseQuery, MessageType::LeaseUnassigned, MessageType::LeaseUnknown, MessageType::LeaseActive];
+
+    for message_type in types {
+        let packet = DhcpPacket::builder()
+            .client_hardware(client_mac)
+            .option(DhcpOption::MessageType(message_type.clone()))
+            .build();
+        let bytes: Vec<u8> = packet.into();
+
+        let messaging = DhcpMessaging::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(messaging.packet().message_type_value(), Some(message_type.clone()));
+        match (&message_type, &messaging) {
+            (MessageType::LeaseQuery, DhcpMessaging::LeaseQuery(_)) => {}
+            (MessageType::LeaseUnassigned, DhcpMessaging::LeaseUnassigned(_)) => {}
+            (MessageType::LeaseUnknown, DhcpMessaging::LeaseUnknown(_)) => {}
+            (MessageType::LeaseActive, DhcpMessaging::LeaseActive(_)) => {}
+            _ => panic!("unexpected messaging variant for {:?}", message_type),
+        }
+        assert_eq!(Vec::<u8>::from(messaging), bytes);
+    }
+}
+
+#[test]
+fn test_ack_into_release_and_decline() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let client_ip = Ipv4Addr::new(1, 2, 3, 4);
+    let server_ip = Ipv4Addr::new(5, 6, 7, 8);
+
+    let ack = || {
+        let offer = DhcpMessaging::discover_with_xid(0x1234, client_mac, None, None, None, None, None, None, None)
+            .into_offer(7200, client_ip, server_ip, None, None, None);
+        let request = offer.into_request(client_mac, 0, Some(client_ip), false, None, None, None, None, None, None, None, None);
+        request.into_ack(7200, client_ip, server_ip, None, None, None, None, None)
+    };
+
+    let release = ack().into_release(server_ip, None);
+    let packet = release.packet();
+    assert_eq!(packet.client_hardware(), &macaddr::MacAddr::from(client_mac));
+    assert_eq!(packet.transaction_id(), 0x1234);
+    assert_eq!(packet.client(), &client_ip);
+    assert_eq!(packet.message_type_value(), Some(MessageType::Release));
+    assert_eq!(packet.server_identifier_ip(), Some(server_ip));
+
+    let decline = ack().into_decline(client_ip, Some(AsciiString::from_ascii("address in use").unwrap()));
+    let packet = decline.packet();
+    assert_eq!(packet.client_hardware(), &macaddr::MacAddr::from(client_mac));
+    assert_eq!(packet.transaction_id(), 0x1234);
+    assert_eq!(packet.message_type_value(), Some(MessageType::Decline));
+    assert_eq!(packet.option(REQUESTED_IP_ADDRESS), Some(&DhcpOption::RequestedIpAddress(client_ip)));
+    assert_eq!(packet.server_identifier_ip(), Some(server_ip));
+}
+
+#[test]
+fn test_offer_keeps_relay_gateway() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let client_ip = Ipv4Addr::new(1, 2, 3, 4);
+    let server_ip = Ipv4Addr::new(5, 6, 7, 8);
+    let relay_ip = Ipv4Addr::new(10, 0, 0, 1);
+
+    let mut discover = DhcpMessaging::discover_with_xid(0x1234, client_mac, None, None, None, None, None, None, None);
+    discover.packet.relay_forward(relay_ip).unwrap();
+
+    let offer = discover.into_offer(7200, client_ip, server_ip, None, None, None);
+    assert_eq!(offer.packet().gateway(), &relay_ip);
+    assert_eq!(offer.relay_target(), Some(relay_ip));
+
+    let offer = DhcpMessaging::discover_with_xid(0x1234, client_mac, None, None, None, None, None, None, None)
+        .into_offer(7200, client_ip, server_ip, None, None, None);
+    assert_eq!(offer.relay_target(), None);
+}
+
+#[test]
+fn test_renew_and_rebind() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let client_ip = Ipv4Addr::new(1, 2, 3, 4);
+
+    for request in [
+        DhcpMessaging::renew_with_xid(0x1234, client_mac, client_ip, None, Some(vec![1, 3, 6])),
+        DhcpMessaging::rebind_with_xid(0x1234, client_mac, client_ip, None, Some(vec![1, 3, 6])),
+    ] {
+        let packet = request.packet();
+        assert_eq!(packet.flags(), &Flags::Unicast);
+        assert_eq!(packet.client(), &client_ip);
+        assert_eq!(packet.transaction_id(), 0x1234);
+        assert_eq!(packet.message_type_value(), Some(MessageType::Request));
+        assert!(packet.option(SERVER_IDENTIFIER).is_none());
+        assert!(packet.option(REQUESTED_IP_ADDRESS).is_none());
+        assert!(packet.validate().is_ok());
+    }
+}
+
+#[test]
+fn test_broadcast_discover_flags() {
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let discover = DhcpMessaging::discover_with_xid(0x1234, client_mac, None, None, None, None, None, None, None);
+    assert!(discover.packet().is_broadcast());
+
+    let bytes: Vec<u8> = DhcpMessaging::Discover(discover).into();
+    assert_eq!(&bytes[10..12], &[0x80, 0x00]);
+    assert!(DhcpPacket::try_from(bytes.as_slice()).unwrap().is_broadcast());
+}
@@ -1,7 +1,14 @@
 use std::net::Ipv4Addr;
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+use std::ops::{Index, IndexMut};
 use ascii::AsciiString;
+use macaddr::{MacAddr, MacAddr6};
 use crate::error::{DhcpResult, DhcpError};
-use crate::convert::{TryToOption, ToOptionBytes, TryIntoOptionMinBytes};
+use crate::convert::{TryToOption, ToOptionBytes, TryIntoOptionMinBytes,
+                     FQDN_FLAG_S, FQDN_FLAG_O, FQDN_FLAG_E, FQDN_FLAG_N, check_dns_names};
 
 
 #[cfg(feature = "with_serde")]
@@ -9,10 +16,16 @@ use serde::{Serialize, Deserialize};
 
 #[cfg(feature = "with_serde")]
 use serde::{Serializer, Deserializer};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 const OPTIONS_SIZE: usize = 256;
 
+/// Lease, renewal or rebinding time `0xffffffff` means infinite (rfc 2131 3.3)
+///
+/// One second longer than the longest finite time, so it compares greater than any finite lease.
+/// Unlike `Duration::MAX` it can still be added to an `Instant` or `SystemTime`.
+pub const INFINITE_TIME: Duration = Duration::from_secs(u32::MAX as u64 + 1);
+
 // RFC 2132
 pub const PAD: u8 = 0;
 pub const SUBNET_MASK: u8 = 1;
@@ -91,23 +104,89 @@ pub const STREET_TALK_SERVER: u8 = 75;
 pub const STREET_TALK_DIRECTORY_ASSISTANCE: u8 = 76;
 pub const END: u8 = 255;
 
+// rfc 2241
+pub const NDS_SERVERS: u8 = 85;
+pub const NDS_TREE_NAME: u8 = 86;
+pub const NDS_CONTEXT: u8 = 87;
+
+// rfc 3004
+pub const USER_CLASS: u8 = 77;
+
+// rfc 4578
+pub const CLIENT_ARCHITECTURE: u8 = 93;
+pub const CLIENT_MACHINE_IDENTIFIER: u8 = 97;
+
+// rfc 4039
+pub const RAPID_COMMIT: u8 = 80;
+
+// rfc 3118
+pub const AUTHENTICATION: u8 = 90;
+
+// rfc 8910
+pub const CAPTIVE_PORTAL: u8 = 114;
+
 // rfc 3046
 pub const RELAY_AGENT_INFORMATION: u8 = 82;
 
+// rfc 4702
+pub const CLIENT_FQDN: u8 = 81;
+
+// rfc 3442
+pub const CLASSLESS_STATIC_ROUTE: u8 = 121;
+/// Microsoft variant of [`CLASSLESS_STATIC_ROUTE`] using the same encoding
+pub const MS_CLASSLESS_STATIC_ROUTE: u8 = 249;
+
+// rfc 3397
+pub const DOMAIN_SEARCH: u8 = 119;
+
+// rfc 3361
+pub const SIP_SERVERS: u8 = 120;
+
+// rfc 5859
+pub const TFTP_SERVER_ADDRESS: u8 = 150;
+
+// rfc 4833
+pub const POSIX_TIMEZONE: u8 = 100;
+pub const TIMEZONE_DATABASE: u8 = 101;
+
+// rfc 5071
+pub const PXE_MAGIC: u8 = 208;
+pub const PXE_CONFIG_FILE: u8 = 209;
+pub const PXE_PATH_PREFIX: u8 = 210;
+pub const PXE_REBOOT_TIME: u8 = 211;
+
+// iPXE
+pub const IPXE_ENCAPSULATED: u8 = 175;
+
 // preserve order
 type DhcpOptionsVec = Vec<Option<DhcpOption>>;
+type DhcpOptionsMap = BTreeMap<u8, Option<DhcpOption>>;
 type Ipv4AddrVec = Vec<Ipv4Addr>;
 
+/// Options as `(tag, data)` pairs without length byte
+pub type RawOptions = Vec<(u8, Vec<u8>)>;
+
 /// Static route
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub struct StaticRoute {
     pub destination: Ipv4Addr,
     pub router: Ipv4Addr,
 }
 
+/// Classless static route
+///
+/// Only the significant octets of `destination` according to `prefix_length` are transmitted.
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
+#[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
+pub struct ClasslessStaticRoute {
+    pub destination: Ipv4Addr,
+    pub prefix_length: u8,
+    pub router: Ipv4Addr,
+}
+
 /// Ipv4 with mask
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub struct Ipv4WithMask {
     pub ipv4addr: Ipv4Addr,
@@ -115,26 +194,38 @@ pub struct Ipv4WithMask {
 }
 
 /// Relay Agent Information
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub enum RelayAgentInformationSubOption {
     AgentCircuit(Vec<u8>),
     AgentRemote(Vec<u8>),
-    Unknown(Vec<u8>),
+    LinkSelection(Ipv4Addr),
+    SubscriberId(AsciiString),
+    Unknown(u8, Vec<u8>),
+}
+
+/// SIP servers, the first data byte selects the encoding
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
+#[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
+pub enum SipServers {
+    DomainNames(Vec<AsciiString>),
+    Addresses(Vec<Ipv4Addr>),
 }
 
 /// NetBios Node Type
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub enum NetBiosNodeType {
     B,
     P,
     M,
     H,
+    /// Unexpected value, kept as is
+    Other(u8),
 }
 
 /// DHCP Overload Option
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub enum Overload {
     Sname,
@@ -145,7 +236,7 @@ pub enum Overload {
 /// DHCP message type
 ///
 /// Required in all DHCP packets
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub enum MessageType {
     Discover,
@@ -156,10 +247,17 @@ pub enum MessageType {
     Nak,
     Release,
     Inform,
+    /// rfc 3203
+    ForceRenew,
+    /// rfc 4388
+    LeaseQuery,
+    LeaseUnassigned,
+    LeaseUnknown,
+    LeaseActive,
 }
 
 /// Client identifier
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub struct ClientIdentifier {
     pub(crate) typ: u8,
@@ -173,25 +271,237 @@ impl ClientIdentifier {
             data,
         }
     }
+
+    /// Hardware type, `0` if the identifier is not a hardware address
+    pub fn typ(&self) -> u8 {
+        self.typ
+    }
+
+    /// Identifier without the type byte
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Ethernet address if the identifier holds one
+    pub fn as_mac(&self) -> Option<MacAddr> {
+        match (self.typ, <[u8; 6]>::try_from(self.data.as_slice())) {
+            (1, Ok(mac)) => Some(MacAddr::V6(MacAddr6::from(mac))),
+            _ => None
+        }
+    }
+}
+
+/// Client machine identifier, type 0 is a 16 byte GUID
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
+pub struct ClientMachineIdentifier {
+    pub typ: u8,
+    pub uuid: [u8; 16],
+}
+
+/// Authentication information, `auth_info` depends on the `protocol` and `algorithm`
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
+pub struct Authentication {
+    pub protocol: u8,
+    pub algorithm: u8,
+    pub rdm: u8,
+    pub replay_detection: u64,
+    pub auth_info: Vec<u8>,
+}
+
+/// Client fully qualified domain name
+///
+/// `flags` holds the `N`, `E`, `O` and `S` bits. `name` is kept as raw bytes because the encoding
+/// depends on the `E` bit.
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
+#[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
+pub struct ClientFqdn {
+    pub(crate) flags: u8,
+    pub(crate) rcode1: u8,
+    pub(crate) rcode2: u8,
+    pub(crate) name: Vec<u8>,
+}
+
+impl ClientFqdn {
+    pub fn new(flags: u8, rcode1: u8, rcode2: u8, name: Vec<u8>) -> Self {
+        Self {
+            flags,
+            rcode1,
+            rcode2,
+            name,
+        }
+    }
+
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    /// `S` bit: client asks the server to update the A RR, or server reports it did
+    pub fn server_update_requested(&self) -> bool {
+        self.flags & FQDN_FLAG_S != 0
+    }
+
+    /// `O` bit: server has overridden the client preference for the `S` bit
+    pub fn server_override(&self) -> bool {
+        self.flags & FQDN_FLAG_O != 0
+    }
+
+    /// `E` bit: domain name uses canonical wire format instead of ASCII
+    pub fn canonical_encoding(&self) -> bool {
+        self.flags & FQDN_FLAG_E != 0
+    }
+
+    /// `N` bit: server should not perform any DNS updates
+    pub fn no_server_update(&self) -> bool {
+        self.flags & FQDN_FLAG_N != 0
+    }
+}
+
+/// Parser configuration
+///
+/// The default accepts everything the wire format allows.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Maximum length of string options like host name or domain name, unlimited if `None`
+    pub max_string_len: Option<usize>,
+}
+
+/// Irregularities tolerated by [`DhcpOptions::from_bytes_lenient`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseNote {
+    /// The buffer ended inside the option with this tag, it was dropped
+    TruncatedLastOption(u8),
+}
+
+/// How [`DhcpOptions::merge_preserving_unknown`] combines two unknown options with the same tag
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnknownMergePolicy {
+    /// Appends the data of the merged option to the existing one (rfc 3396 style)
+    Concatenate,
+    /// Keeps the existing option untouched
+    KeepExisting,
 }
 
 /// Contains all DHCP Options
 ///
-/// Preserves option
+/// Preserves option, keyed and ordered by tag so `End` is always last.
 ///
 /// Use `From<Vec<DhcpOption>>`, [`DhcpOptions::new_with_options`] or [`DhcpOptions::from_bytes`] for creation.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub struct DhcpOptions {
+    // a `None` value is only left behind by `IndexMut` or `iter_mut`, all accessors and `clone` skip it
     #[cfg_attr(feature = "with_serde", serde(serialize_with = "DhcpOptions::serialize_options", deserialize_with = "DhcpOptions::deserialize_options"))]
-    options: DhcpOptionsVec,
+    options: DhcpOptionsMap,
+
+}
+
+/// Clones the present options only
+impl Clone for DhcpOptions {
+    fn clone(&self) -> Self {
+        Self {
+            options: self.options.iter()
+                .filter(|(_, option)| option.is_some())
+                .map(|(tag, option)| (*tag, option.clone()))
+                .collect()
+        }
+    }
+}
+
+/// Equal if the same options are present
+impl PartialEq for DhcpOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.options() == other.options()
+    }
+}
+
+/// Access an option by its tag, e.g. `options[SUBNET_MASK]`
+///
+/// Indexing a tag that was never set yields `None`.
+impl Index<u8> for DhcpOptions {
+    type Output = Option<DhcpOption>;
+
+    fn index(&self, tag: u8) -> &Self::Output {
+        const NONE: &Option<DhcpOption> = &None;
+        self.options.get(&tag).unwrap_or(NONE)
+    }
+}
+
+/// Mutable access to an option by its tag
+///
+/// Assigning `Some` with an option of a different tag breaks the collection, use
+/// [`DhcpOptions::upsert`] to add options.
+impl IndexMut<u8> for DhcpOptions {
+    fn index_mut(&mut self, tag: u8) -> &mut Self::Output {
+        self.options.entry(tag).or_insert(None)
+    }
+}
+
+impl Default for DhcpOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+/// [`DhcpOptions`] serialized as map from tag to option, e.g. `{"53":{"MessageType":"Discover"}}`
+///
+/// Unlike the list produced by serializing [`DhcpOptions`] directly, an option can be looked up by tag.
+/// Deserialization fails if a key does not match the tag of its option.
+#[cfg(feature = "with_serde")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DhcpOptionsByTag(
+    #[serde(serialize_with = "DhcpOptionsByTag::serialize_options", deserialize_with = "DhcpOptionsByTag::deserialize_options")]
+    pub DhcpOptions
+);
+
+#[cfg(feature = "with_serde")]
+impl DhcpOptionsByTag {
+    fn serialize_options<S>(data: &DhcpOptions, s: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+    {
+        data.as_map().serialize(s)
+    }
+
+    fn deserialize_options<'de, D>(deserializer: D) -> Result<DhcpOptions, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        let options: BTreeMap<u8, DhcpOption> = Deserialize::deserialize(deserializer)?;
+        options.into_iter()
+            .map(|(tag, option)| if option.tag() == tag {
+                Ok(option)
+            } else {
+                Err(serde::de::Error::custom(format!("key {} does not match option {}", tag, option.tag())))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "with_serde")]
+impl From<DhcpOptions> for DhcpOptionsByTag {
+    fn from(options: DhcpOptions) -> Self {
+        Self(options)
+    }
+}
+
+#[cfg(feature = "with_serde")]
+impl From<DhcpOptionsByTag> for DhcpOptions {
+    fn from(options: DhcpOptionsByTag) -> Self {
+        options.0
+    }
 }
 
 impl DhcpOptions {
     pub fn new() -> Self {
         Self {
-            options: Self::new_with_options(vec![])
+            options: DhcpOptionsMap::new()
         }
     }
 
@@ -209,71 +519,179 @@ impl DhcpOptions {
         options
     }
 
+    /// Options keyed by tag, a later option replaces an earlier one with the same tag
+    fn map_with_options(init_options: Vec<DhcpOption>) -> DhcpOptionsMap {
+        init_options.into_iter()
+            .map(|o| (o.tag(), Some(o)))
+            .collect()
+    }
+
     #[cfg(feature = "with_serde")]
-    fn serialize_options<S>(data: &[Option<DhcpOption>], s: S) -> Result<S::Ok, S::Error>
+    fn serialize_options<S>(data: &DhcpOptionsMap, s: S) -> Result<S::Ok, S::Error>
         where
             S: Serializer,
     {
-        // fixed vec is only used for easier access
-        let none_removed: Vec<&DhcpOption> = data.iter().filter_map(Option::as_ref).collect();
+        let none_removed: Vec<&DhcpOption> = data.values().filter_map(Option::as_ref).collect();
         none_removed.serialize(s)
     }
 
     #[cfg(feature = "with_serde")]
-    fn deserialize_options<'de, D>(deserializer: D) -> Result<Vec<Option<DhcpOption>>, D::Error>
+    fn deserialize_options<'de, D>(deserializer: D) -> Result<DhcpOptionsMap, D::Error>
         where
             D: Deserializer<'de>,
     {
-        // prepare empty fixed vector
-        let mut option_fixed = Self::new_with_options(vec![]);
-
-        // deserialize options
         let options: Vec<DhcpOption> = Deserialize::deserialize(deserializer)?;
-
-        // insert options using tag == index
-        for option in options {
-            let tag = option.tag() as usize;
-            option_fixed[tag] = Some(option);
-        }
-
-        Ok(option_fixed)
+        Ok(Self::map_with_options(options))
     }
 
-    /// Generates bytes from all defined [`DhcpOption`]
+    /// Generates bytes from all defined [`DhcpOption`] in ascending tag order
     /// Mostly used in conjunction with [`DhcpPacket`](crate::DhcpPacket)
     pub fn to_bytes(&self) -> Vec<u8> {
-        self.options.iter().filter_map(|o| {
-            o.as_ref().map(|s| s.to_bytes())
-        }).flatten().collect()
+        let mut bytes = vec![];
+        self.write_to(&mut bytes);
+        bytes
+    }
+
+    /// Appends the bytes of [`DhcpOptions::to_bytes`] to `buf`
+    pub fn write_to(&self, buf: &mut Vec<u8>) {
+        for option in self.options() {
+            option.write_to(buf);
+        }
     }
 
     /// Generate [`DhcpOptions`] by parsing the given byte slice
     /// Mostly used in conjunction with [`DhcpPacket`](crate::DhcpPacket)
-    pub fn from_bytes(mut bytes: &[u8]) -> DhcpResult<DhcpOptions> {
-        let mut options = Self::new_with_options(vec![]);
+    pub fn from_bytes(bytes: &[u8]) -> DhcpResult<DhcpOptions> {
+        Self::from_bytes_with(bytes, &ParseOptions::default())
+    }
+
+    /// Same as [`DhcpOptions::from_bytes`] using the given parser configuration
+    pub fn from_bytes_with(bytes: &[u8], parse_options: &ParseOptions) -> DhcpResult<DhcpOptions> {
+        Self::from_bytes_with_raw(bytes, parse_options).map(|(options, _, _)| options)
+    }
+
+    /// Same as [`DhcpOptions::from_bytes`] but also returns the number of bytes consumed
+    ///
+    /// The length includes the `End` option but not any pad bytes trailing it.
+    pub fn from_bytes_with_len(bytes: &[u8]) -> DhcpResult<(DhcpOptions, usize)> {
+        Self::from_bytes_with_raw(bytes, &ParseOptions::default()).map(|(options, _, length)| (options, length))
+    }
+
+    /// Same as [`DhcpOptions::from_bytes_with`] but also returns the raw `(tag, data)` pairs in wire order
+    /// and the number of bytes consumed
+    pub(crate) fn from_bytes_with_raw(bytes: &[u8], parse_options: &ParseOptions) -> DhcpResult<(DhcpOptions, RawOptions, usize)> {
+        let (raw, length) = Self::raw_from_bytes(bytes)?;
+        let options = Self::from_raw(&raw, parse_options)?;
+        Ok((options, raw, length))
+    }
+
+    /// Splits options up to `End` into raw `(tag, data)` pairs without parsing the data
+    pub(crate) fn raw_from_bytes(mut bytes: &[u8]) -> DhcpResult<(RawOptions, usize)> {
+        let mut raw = vec![];
+        let total = bytes.len();
 
         loop {
-            let tag = bytes[0];
+            let tag = *bytes.first().ok_or(DhcpError::UnexpectedEndOfOptions)?;
             if tag == PAD {
                 bytes = &bytes[1..];
             } else if tag == END {
-                options[END as usize] = Some(DhcpOption::End);
-                return Ok(Self {
-                    options,
-                });
+                return Ok((raw, total - bytes.len() + 1));
             } else {
-                let data_length = bytes[1] as usize;
+                let data_length = *bytes.get(1).ok_or(DhcpError::OptionParseError(tag))? as usize;
                 let data_start = 2; // 1 tag + 1 length
                 let data_end = data_length + data_start; // take [length] bytes
-                let data = &bytes[data_start..data_end];
+                let data = bytes.get(data_start..data_end).ok_or(DhcpError::OptionParseError(tag))?;
                 bytes = &bytes[data_end..]; // leftover bytes
-                options[tag as usize] = Some(DhcpOption::from_bytes(tag, data_length, data)?);
+                raw.push((tag, data.to_vec()));
             }
         }
     }
 
+    /// Parses raw options terminated by `End`
+    pub(crate) fn from_raw(raw: &[(u8, Vec<u8>)], parse_options: &ParseOptions) -> DhcpResult<DhcpOptions> {
+        let mut options = Self::concatenated(raw, parse_options)?;
+        options.upsert(DhcpOption::End);
+        Ok(options)
+    }
+
+    /// Parses raw options, the data of options sharing a tag is concatenated first (rfc 3396)
+    fn concatenated(raw: &[(u8, Vec<u8>)], parse_options: &ParseOptions) -> DhcpResult<DhcpOptions> {
+        let mut data: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+        for (tag, part) in raw {
+            data.entry(*tag).or_default().extend_from_slice(part);
+        }
+
+        let mut options = Self::new();
+        for (tag, data) in data {
+            options.upsert(DhcpOption::from_bytes_with(tag, data.len(), &data, parse_options)?);
+        }
+
+        Ok(options)
+    }
+
+    /// Same as [`DhcpOptions::from_bytes`] but tolerates a buffer ending mid-option, e.g. a snaplen-limited capture
+    ///
+    /// Options parsed before the cut are returned together with a [`ParseNote`] describing what was dropped.
+    pub fn from_bytes_lenient(mut bytes: &[u8]) -> DhcpResult<(DhcpOptions, Vec<ParseNote>)> {
+        let mut raw = vec![];
+        let mut notes = vec![];
+        let mut end = false;
+
+        while let Some(&tag) = bytes.first() {
+            if tag == PAD {
+                bytes = &bytes[1..];
+            } else if tag == END {
+                end = true;
+                break;
+            } else {
+                let data = bytes.get(1)
+                    .and_then(|&length| bytes.get(2..2 + length as usize));
+                let data = match data {
+                    Some(data) => data,
+                    None => {
+                        notes.push(ParseNote::TruncatedLastOption(tag));
+                        break;
+                    }
+                };
+                raw.push((tag, data.to_vec()));
+                bytes = &bytes[2 + data.len()..];
+            }
+        }
+
+        let mut options = Self::concatenated(&raw, &ParseOptions::default())?;
+        if end {
+            options.upsert(DhcpOption::End);
+        }
+
+        Ok((options, notes))
+    }
+
+    /// All defined options as `(tag, data)` pairs, encoded from the typed values
+    ///
+    /// `Pad` and `End` are skipped since they carry no data. Options split into multiple instances
+    /// (rfc 3396) yield one pair per instance, the same way they are parsed.
+    pub fn to_raw(&self) -> RawOptions {
+        let mut raw = vec![];
+
+        for option in self.options() {
+            if matches!(option, DhcpOption::Pad | DhcpOption::End) {
+                continue;
+            }
+
+            let bytes = option.to_bytes();
+            let mut rest = bytes.as_slice();
+            while let [tag, length, tail @ ..] = rest {
+                let (data, next) = tail.split_at(*length as usize);
+                raw.push((*tag, data.to_vec()));
+                rest = next;
+            }
+        }
+
+        raw
+    }
+
     pub fn parameter_request_list(&self) -> Option<&[u8]> {
-        if let Some(DhcpOption::ParameterRequestList(data)) = &self.options[CLIENT_IDENTIFIER as usize] {
+        if let Some(DhcpOption::ParameterRequestList(data)) = self.option(PARAMETER_REQUEST_LIST) {
             Some(data.as_slice())
         } else {
             None
@@ -284,106 +702,283 @@ impl DhcpOptions {
         self.option(MESSAGE_TYPE)
     }
 
+    /// Value of the message type option (53)
+    pub fn message_type_value(&self) -> Option<MessageType> {
+        match self.message_type() {
+            Some(DhcpOption::MessageType(typ)) => Some(typ.clone()),
+            _ => None
+        }
+    }
+
     pub fn option(&self, tag: u8) -> Option<&DhcpOption> {
-        self.options[tag as usize].as_ref()
+        self.options.get(&tag).and_then(Option::as_ref)
     }
 
     pub fn option_mut(&mut self, tag: u8) -> Option<&mut DhcpOption> {
-        self.options[tag as usize].as_mut()
+        self.options.get_mut(&tag).and_then(Option::as_mut)
     }
 
     /// A reference to all defined [`DhcpOption`]
     pub fn options(&self) -> Vec<&DhcpOption> {
-        self.options.iter().filter_map(Option::as_ref).collect()
+        self.options.values().filter_map(Option::as_ref).collect()
+    }
+
+    /// Sets the broadcast address option (28) of `network` using the subnet mask option (1)
+    ///
+    /// Fails if the subnet mask is missing or not contiguous.
+    pub fn set_broadcast_from_mask(&mut self, network: Ipv4Addr) -> DhcpResult<()> {
+        let mask = match self.option(SUBNET_MASK) {
+            Some(DhcpOption::SubnetMask(mask)) => *mask,
+            _ => return Err(DhcpError::OptionNotExist(SUBNET_MASK)),
+        };
+        mask_prefix_length(mask).ok_or(DhcpError::OptionInvalidValueError(SUBNET_MASK))?;
+        let mask = u32::from(mask);
+
+        self.upsert(DhcpOption::BroadcastAddress(Ipv4Addr::from(u32::from(network) | !mask)));
+        Ok(())
+    }
+
+    /// Address of the server identifier option (54)
+    pub fn server_identifier(&self) -> Option<Ipv4Addr> {
+        match self.option(SERVER_IDENTIFIER) {
+            Some(DhcpOption::ServerIdentifier(ip)) => Some(*ip),
+            _ => None
+        }
+    }
+
+    /// Address of the requested ip address option (50)
+    pub fn requested_ip(&self) -> Option<Ipv4Addr> {
+        match self.option(REQUESTED_IP_ADDRESS) {
+            Some(DhcpOption::RequestedIpAddress(ip)) => Some(*ip),
+            _ => None
+        }
+    }
+
+    /// First address of the router option (3), clients usually use it as default gateway
+    pub fn first_router(&self) -> Option<Ipv4Addr> {
+        match self.option(ROUTER) {
+            Some(DhcpOption::Router(routers)) => routers.first().copied(),
+            _ => None
+        }
+    }
+
+    /// First address of the domain name server option (6)
+    pub fn first_dns(&self) -> Option<Ipv4Addr> {
+        match self.option(DOMAIN_NAME_SERVER) {
+            Some(DhcpOption::DomainNameServer(servers)) => servers.first().copied(),
+            _ => None
+        }
+    }
+
+    /// First address of the network time protocol servers option (42)
+    pub fn first_ntp(&self) -> Option<Ipv4Addr> {
+        match self.option(NETWORK_TIME_PROTOCOL_SERVERS) {
+            Some(DhcpOption::NetworkTimeProtocolServers(servers)) => servers.first().copied(),
+            _ => None
+        }
+    }
+
+    /// Lease time option (51), [`INFINITE_TIME`] for an infinite lease
+    pub fn lease_time(&self) -> Option<Duration> {
+        match self.option(IP_ADDRESS_LEASE_TIME) {
+            Some(DhcpOption::IpAddressLeaseTime(seconds)) => Some(duration_from_seconds(*seconds)),
+            _ => None
+        }
+    }
+
+    /// Renewal (T1) time option (58), [`INFINITE_TIME`] if infinite
+    pub fn renewal_time(&self) -> Option<Duration> {
+        match self.option(RENEWAL_TIME_VALUE) {
+            Some(DhcpOption::RenewalTimeValue(seconds)) => Some(duration_from_seconds(*seconds)),
+            _ => None
+        }
+    }
+
+    /// Rebinding (T2) time option (59), [`INFINITE_TIME`] if infinite
+    pub fn rebinding_time(&self) -> Option<Duration> {
+        match self.option(REBINDING_TIME_VALUE) {
+            Some(DhcpOption::RebindingTimeValue(seconds)) => Some(duration_from_seconds(*seconds)),
+            _ => None
+        }
+    }
+
+    /// Text of the message option (56), e.g. the reason of a NAK
+    pub fn message(&self) -> Option<&AsciiString> {
+        match self.option(MESSAGE) {
+            Some(DhcpOption::Message(message)) => Some(message),
+            _ => None
+        }
+    }
+
+    /// Whether the rapid commit option is present
+    pub fn has_rapid_commit(&self) -> bool {
+        self.option(RAPID_COMMIT).is_some()
+    }
+
+    /// Tag and data of every [`DhcpOption::Unknown`]
+    pub fn unknown_options(&self) -> Vec<(u8, &[u8])> {
+        self.options().into_iter().filter_map(|o| match o {
+            DhcpOption::Unknown(tag, data) => Some((*tag, data.as_slice())),
+            _ => None
+        }).collect()
+    }
+
+    /// All defined [`DhcpOption`] keyed by tag
+    pub fn as_map(&self) -> BTreeMap<u8, &DhcpOption> {
+        self.options().into_iter().map(|o| (o.tag(), o)).collect()
+    }
+
+    /// Whether an option with this tag is present
+    pub fn contains(&self, tag: u8) -> bool {
+        self.option(tag).is_some()
+    }
+
+    /// Number of present options
+    pub fn len(&self) -> usize {
+        self.options.values().filter_map(Option::as_ref).count()
+    }
+
+    /// Whether no option is present
+    pub fn is_empty(&self) -> bool {
+        self.options.values().filter_map(Option::as_ref).next().is_none()
     }
 
     /// A list of all [`DhcpOption`] as mutable reference
     pub fn options_mut(&mut self) -> Vec<&mut DhcpOption> {
-        self.options.iter_mut().filter_map(Option::as_mut).collect()
+        self.options.values_mut().filter_map(Option::as_mut).collect()
     }
 
     /// Insert or update a single [`DhcpOption`]
     pub fn upsert(&mut self, option: DhcpOption) {
-        let tag = option.tag() as usize;
-        self.options[tag] = Some(option);
+        self.options.insert(option.tag(), Some(option));
     }
 
     /// Insert or update a single [`DhcpOption`] wrapped as [`Option`]
     pub fn upsert_option(&mut self, option: Option<DhcpOption>) {
         if let Some(o) = option {
-            let tag = o.tag() as usize;
-            self.options[tag] = Some(o);
+            self.upsert(o);
         }
     }
 
     /// Inserts all defined DhcpOptions to the existing collection
     pub fn merge(&mut self, options: Self) {
-        for o in options.into_iter() {
-            if let Some(option) = &o {
-                let tag = option.tag() as usize;
-                self.options[tag] = o
+        for option in options.into_iter().flatten() {
+            self.upsert(option);
+        }
+    }
+
+    /// Same as [`DhcpOptions::merge`] but [`DhcpOption::Unknown`] options present on both sides are
+    /// combined according to `policy` instead of being overwritten
+    pub fn merge_preserving_unknown(&mut self, options: Self, policy: UnknownMergePolicy) {
+        for option in options.into_iter().flatten() {
+            match (self.options.entry(option.tag()).or_insert(None), option) {
+                (Some(DhcpOption::Unknown(_, existing)), DhcpOption::Unknown(_, data)) => match policy {
+                    UnknownMergePolicy::Concatenate => existing.extend(data),
+                    UnknownMergePolicy::KeepExisting => {}
+                },
+                (slot, option) => *slot = Some(option),
             }
         }
     }
 
+    /// Options whose tag is in the parameter request list (55) of a client
+    ///
+    /// Message type, server identifier and `End` are always kept.
+    pub fn filtered_by_prl(&self, prl: &[u8]) -> DhcpOptions {
+        self.iter_present()
+            .filter(|(tag, _)| matches!(*tag, MESSAGE_TYPE | SERVER_IDENTIFIER | END) || prl.contains(tag))
+            .map(|(_, option)| option.clone())
+            .collect()
+    }
+
+    /// First [`DhcpOption`] matching the predicate
+    pub fn find<F: Fn(&DhcpOption) -> bool>(&self, f: F) -> Option<&DhcpOption> {
+        self.options.values().filter_map(Option::as_ref).find(|o| f(o))
+    }
+
+    /// Whether any [`DhcpOption`] matches the predicate
+    pub fn any<F: Fn(&DhcpOption) -> bool>(&self, f: F) -> bool {
+        self.find(f).is_some()
+    }
+
+    /// Apply `f` to the [`DhcpOption`] with the given tag if present
+    ///
+    /// Returns whether `f` was called.
+    pub fn update<F: FnOnce(&mut DhcpOption)>(&mut self, tag: u8, f: F) -> bool {
+        if let Some(option) = self.option_mut(tag) {
+            f(option);
+            true
+        } else {
+            false
+        }
+    }
+
     /// Remove a single [`DhcpOption`]
     pub fn remove(&mut self, tag: u8) {
-        self.options[tag as usize] = None;
+        self.options.remove(&tag);
     }
 
-    /// Iterator over all [`DhcpOption`]
+    /// Iterator over all [`DhcpOption`] in ascending tag order, one slot per tag
     pub fn iter(&mut self) -> impl Iterator<Item=&Option<DhcpOption>> {
-        self.options.iter()
+        let options = &*self;
+        (0..OPTIONS_SIZE).map(move |tag| &options[tag as u8])
     }
 
-    /// Mutable iteration over all [`DhcpOption`]
+    /// Present options with their tag in ascending tag order
+    pub fn iter_present(&self) -> impl Iterator<Item=(u8, &DhcpOption)> {
+        self.options.iter().filter_map(|(tag, option)| option.as_ref().map(|o| (*tag, o)))
+    }
+
+    /// Mutable iteration over the present [`DhcpOption`] in ascending tag order
+    ///
+    /// Setting an item to `None` removes the option, use [`DhcpOptions::upsert`] to add options.
     pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut Option<DhcpOption>> {
-        self.options.iter_mut()
+        self.options.values_mut().filter(|option| option.is_some())
     }
 
     /// Try to extract option value
     pub fn try_ascii_option(&self, tag: u8) -> DhcpResult<AsciiString> {
-        self.option(tag).ok_or_else(|| DhcpError::OptionNotExist(tag))?.try_to_ascii()
+        self.option(tag).ok_or(DhcpError::OptionNotExist(tag))?.try_to_ascii()
     }
 
     /// Try to extract option value
     pub fn try_ipv4_option(&self, tag: u8) -> DhcpResult<Ipv4Addr> {
-        self.option(tag).ok_or_else(|| DhcpError::OptionNotExist(tag))?.try_to_ipv4()
+        self.option(tag).ok_or(DhcpError::OptionNotExist(tag))?.try_to_ipv4()
     }
 
     /// Try to extract option value
     pub fn try_ipv4vec_option(&self, tag: u8) -> DhcpResult<Ipv4AddrVec> {
-        self.option(tag).ok_or_else(|| DhcpError::OptionNotExist(tag))?.try_to_ipv4vec()
+        self.option(tag).ok_or(DhcpError::OptionNotExist(tag))?.try_to_ipv4vec()
     }
 
     /// Try to extract option value
     pub fn try_u8_option(&self, tag: u8) -> DhcpResult<u8> {
-        self.option(tag).ok_or_else(|| DhcpError::OptionNotExist(tag))?.try_to_u8()
+        self.option(tag).ok_or(DhcpError::OptionNotExist(tag))?.try_to_u8()
     }
 
     /// Try to extract option value
     pub fn try_u16_option(&self, tag: u8) -> DhcpResult<u16> {
-        self.option(tag).ok_or_else(|| DhcpError::OptionNotExist(tag))?.try_to_u16()
+        self.option(tag).ok_or(DhcpError::OptionNotExist(tag))?.try_to_u16()
     }
 
     /// Try to extract option value
     pub fn try_u32_option(&self, tag: u8) -> DhcpResult<u32> {
-        self.option(tag).ok_or_else(|| DhcpError::OptionNotExist(tag))?.try_to_u32()
+        self.option(tag).ok_or(DhcpError::OptionNotExist(tag))?.try_to_u32()
     }
 
     /// Try to extract option value
     pub fn try_vec_u8_option(&self, tag: u8) -> DhcpResult<Vec<u8>> {
-        self.option(tag).ok_or_else(|| DhcpError::OptionNotExist(tag))?.try_to_vec_u8()
+        self.option(tag).ok_or(DhcpError::OptionNotExist(tag))?.try_to_vec_u8()
     }
 
     /// Try to extract option value
     pub fn try_to_i32(&self, tag: u8) -> DhcpResult<i32> {
-        self.option(tag).ok_or_else(|| DhcpError::OptionNotExist(tag))?.try_to_i32()
+        self.option(tag).ok_or(DhcpError::OptionNotExist(tag))?.try_to_i32()
     }
 
     /// Try to extract option value
     pub fn try_to_bool(&self, tag: u8) -> DhcpResult<bool> {
-        self.option(tag).ok_or_else(|| DhcpError::OptionNotExist(tag))?.try_to_bool()
+        self.option(tag).ok_or(DhcpError::OptionNotExist(tag))?.try_to_bool()
     }
 }
 
@@ -391,15 +986,26 @@ impl IntoIterator for DhcpOptions {
     type Item = Option<DhcpOption>;
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.options.into_iter()
+    fn into_iter(mut self) -> Self::IntoIter {
+        (0..OPTIONS_SIZE)
+            .map(|tag| self.options.remove(&(tag as u8)).flatten())
+            .collect::<DhcpOptionsVec>()
+            .into_iter()
     }
 }
 
 impl From<Vec<DhcpOption>> for DhcpOptions {
     fn from(o: Vec<DhcpOption>) -> Self {
         Self {
-            options: Self::new_with_options(o)
+            options: Self::map_with_options(o)
+        }
+    }
+}
+
+impl FromIterator<DhcpOption> for DhcpOptions {
+    fn from_iter<T: IntoIterator<Item=DhcpOption>>(iter: T) -> Self {
+        Self {
+            options: Self::map_with_options(iter.into_iter().collect())
         }
     }
 }
@@ -407,8 +1013,8 @@ impl From<Vec<DhcpOption>> for DhcpOptions {
 impl From<Vec<Option<DhcpOption>>> for DhcpOptions {
     fn from(o: Vec<Option<DhcpOption>>) -> Self {
         Self {
-            options: Self::new_with_options(o.into_iter()
-                .filter_map(|s| s)
+            options: Self::map_with_options(o.into_iter()
+                .flatten()
                 .collect()
             )
         }
@@ -417,14 +1023,14 @@ impl From<Vec<Option<DhcpOption>>> for DhcpOptions {
 
 impl From<Option<DhcpOptions>> for DhcpOptions {
     fn from(o: Option<DhcpOptions>) -> Self {
-        o.unwrap_or_else(|| DhcpOptions::new())
+        o.unwrap_or_default()
     }
 }
 
 /// Represents a single Dhcp Option
 ///
 /// Use [`DhcpOption::from_bytes`] or [`DhcpOption::to_bytes`] to create/convert.
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "with_serde", derive(Serialize, Deserialize))]
 pub enum DhcpOption {
     Pad,
@@ -456,6 +1062,7 @@ pub enum DhcpOption {
     InterfaceMtu(u16),
     AllSubnetsLocal(bool),
     BroadcastAddress(Ipv4Addr),
+    PerformMaskDiscovery(bool),
     MaskSupplier(bool),
     PerformRouterDiscovery(bool),
     RouterSolicitationAddress(Ipv4Addr),
@@ -503,23 +1110,203 @@ pub enum DhcpOption {
     StreetTalkDirectoryAssistanceServer(Ipv4AddrVec),
     End,
     RelayAgentInformation(Vec<RelayAgentInformationSubOption>),
+    ClientFqdn(ClientFqdn),
+    ClasslessStaticRoute(Vec<ClasslessStaticRoute>),
+    MsClasslessStaticRoute(Vec<ClasslessStaticRoute>),
+    TftpServerAddress(Ipv4AddrVec),
+    NdsServers(Ipv4AddrVec),
+    NdsTreeName(Vec<u8>),
+    NdsContext(Vec<u8>),
+    PosixTimezone(AsciiString),
+    TimezoneDatabase(AsciiString),
+    PxeMagic(Vec<u8>),
+    PxeConfigFile(AsciiString),
+    PxePathPrefix(AsciiString),
+    PxeRebootTime(u32),
+    DomainSearch(Vec<AsciiString>),
+    UserClass(Vec<Vec<u8>>),
+    SipServers(SipServers),
+    CaptivePortal(AsciiString),
+    ClientArchitecture(Vec<u16>),
+    ClientMachineIdentifier(ClientMachineIdentifier),
+    RapidCommit,
+    Authentication(Authentication),
     Unknown(u8, Vec<u8>),
 }
 
 
+/// Human readable option, e.g. `SubnetMask(255.255.255.0)` or `Router([192.168.1.1])`
+///
+/// Raw data of vendor and unknown options is printed as hex.
+impl Display for DhcpOption {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        fn join<T: Display>(values: &[T]) -> String {
+            values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+        }
+        fn hex(data: &[u8]) -> String {
+            data.iter().map(|b| format!("{:02x}", b)).collect()
+        }
+
+        match self {
+            DhcpOption::Pad => write!(f, "Pad"),
+            DhcpOption::SubnetMask(v) => write!(f, "SubnetMask({})", v),
+            DhcpOption::TimeOffset(v) => write!(f, "TimeOffset({})", v),
+            DhcpOption::Router(v) => write!(f, "Router([{}])", join(v)),
+            DhcpOption::TimeServer(v) => write!(f, "TimeServer([{}])", join(v)),
+            DhcpOption::NameServer(v) => write!(f, "NameServer([{}])", join(v)),
+            DhcpOption::DomainNameServer(v) => write!(f, "DomainNameServer([{}])", join(v)),
+            DhcpOption::LogServer(v) => write!(f, "LogServer([{}])", join(v)),
+            DhcpOption::CookieServer(v) => write!(f, "CookieServer([{}])", join(v)),
+            DhcpOption::LPRServer(v) => write!(f, "LPRServer([{}])", join(v)),
+            DhcpOption::ImpressServer(v) => write!(f, "ImpressServer([{}])", join(v)),
+            DhcpOption::ResourceLocationServer(v) => write!(f, "ResourceLocationServer([{}])", join(v)),
+            DhcpOption::HostName(v) => write!(f, "HostName({})", v),
+            DhcpOption::BootFileSize(v) => write!(f, "BootFileSize({})", v),
+            DhcpOption::MeritDumpFile(v) => write!(f, "MeritDumpFile({})", v),
+            DhcpOption::DomainName(v) => write!(f, "DomainName({})", v),
+            DhcpOption::SwapServer(v) => write!(f, "SwapServer({})", v),
+            DhcpOption::RootPath(v) => write!(f, "RootPath({})", v),
+            DhcpOption::ExtensionPath(v) => write!(f, "ExtensionPath({})", v),
+            DhcpOption::IpForwarding(v) => write!(f, "IpForwarding({})", v),
+            DhcpOption::NonLocalSourceRouting(v) => write!(f, "NonLocalSourceRouting({})", v),
+            DhcpOption::PolicyFilter(v) => write!(f, "PolicyFilter({:?})", v),
+            DhcpOption::MaximumDatagramReassemblySize(v) => write!(f, "MaximumDatagramReassemblySize({})", v),
+            DhcpOption::DefaultIpTTL(v) => write!(f, "DefaultIpTTL({})", v),
+            DhcpOption::PathMtuAgingTimeout(v) => write!(f, "PathMtuAgingTimeout({})", v),
+            DhcpOption::PathMtuPlateauTable(v) => write!(f, "PathMtuPlateauTable({:?})", v),
+            DhcpOption::InterfaceMtu(v) => write!(f, "InterfaceMtu({})", v),
+            DhcpOption::AllSubnetsLocal(v) => write!(f, "AllSubnetsLocal({})", v),
+            DhcpOption::BroadcastAddress(v) => write!(f, "BroadcastAddress({})", v),
+            DhcpOption::PerformMaskDiscovery(v) => write!(f, "PerformMaskDiscovery({})", v),
+            DhcpOption::MaskSupplier(v) => write!(f, "MaskSupplier({})", v),
+            DhcpOption::PerformRouterDiscovery(v) => write!(f, "PerformRouterDiscovery({})", v),
+            DhcpOption::RouterSolicitationAddress(v) => write!(f, "RouterSolicitationAddress({})", v),
+            DhcpOption::StaticRoute(v) => write!(f, "StaticRoute({:?})", v),
+            DhcpOption::TrailerEncapsulation(v) => write!(f, "TrailerEncapsulation({})", v),
+            DhcpOption::ArpCacheTimeout(v) => write!(f, "ArpCacheTimeout({})", v),
+            DhcpOption::EthernetEncapsulation(v) => write!(f, "EthernetEncapsulation({})", v),
+            DhcpOption::TcpDefaultTTL(v) => write!(f, "TcpDefaultTTL({})", v),
+            DhcpOption::TcpKeepAliveInterval(v) => write!(f, "TcpKeepAliveInterval({})", v),
+            DhcpOption::TcpKeepAliveGarbage(v) => write!(f, "TcpKeepAliveGarbage({})", v),
+            DhcpOption::NetworkInformationServiceDomain(v) => write!(f, "NetworkInformationServiceDomain({})", v),
+            DhcpOption::NetworkInformationServers(v) => write!(f, "NetworkInformationServers([{}])", join(v)),
+            DhcpOption::NetworkTimeProtocolServers(v) => write!(f, "NetworkTimeProtocolServers([{}])", join(v)),
+            DhcpOption::VendorSpecific(v) => write!(f, "VendorSpecific({})", hex(v)),
+            DhcpOption::NetBiosOverTcpIpNameServer(v) => write!(f, "NetBiosOverTcpIpNameServer([{}])", join(v)),
+            DhcpOption::NetBiosOverTcpIpDatagramDistributionServer(v) => write!(f, "NetBiosOverTcpIpDatagramDistributionServer([{}])", join(v)),
+            DhcpOption::NetBiosOverTcpIpNodeType(v) => write!(f, "NetBiosOverTcpIpNodeType({:?})", v),
+            DhcpOption::NetBiosOverTcpIpScope(v) => write!(f, "NetBiosOverTcpIpScope({})", v),
+            DhcpOption::XWindowSystemFontServer(v) => write!(f, "XWindowSystemFontServer([{}])", join(v)),
+            DhcpOption::XWindowSystemDisplayManager(v) => write!(f, "XWindowSystemDisplayManager([{}])", join(v)),
+            DhcpOption::RequestedIpAddress(v) => write!(f, "RequestedIpAddress({})", v),
+            DhcpOption::IpAddressLeaseTime(v) => write!(f, "IpAddressLeaseTime({})", v),
+            DhcpOption::OptionOverload(v) => write!(f, "OptionOverload({:?})", v),
+            DhcpOption::MessageType(v) => write!(f, "MessageType({:?})", v),
+            DhcpOption::ServerIdentifier(v) => write!(f, "ServerIdentifier({})", v),
+            DhcpOption::ParameterRequestList(v) => write!(f, "ParameterRequestList({:?})", v),
+            DhcpOption::Message(v) => write!(f, "Message({})", v),
+            DhcpOption::MaximumDhcpMessageSize(v) => write!(f, "MaximumDhcpMessageSize({})", v),
+            DhcpOption::RenewalTimeValue(v) => write!(f, "RenewalTimeValue({})", v),
+            DhcpOption::RebindingTimeValue(v) => write!(f, "RebindingTimeValue({})", v),
+            DhcpOption::VendorClassIdentifier(v) => write!(f, "VendorClassIdentifier({})", hex(v)),
+            DhcpOption::ClientIdentifier(v) => write!(f, "ClientIdentifier({:?})", v),
+            DhcpOption::NetworkInformationServicePlusDomain(v) => write!(f, "NetworkInformationServicePlusDomain({})", v),
+            DhcpOption::NetworkInformationServicePlusServer(v) => write!(f, "NetworkInformationServicePlusServer([{}])", join(v)),
+            DhcpOption::TftpServer(v) => write!(f, "TftpServer({})", v),
+            DhcpOption::BootFileName(v) => write!(f, "BootFileName({})", v),
+            DhcpOption::MobileIpHomeAgent(v) => write!(f, "MobileIpHomeAgent([{}])", join(v)),
+            DhcpOption::SmtpServer(v) => write!(f, "SmtpServer([{}])", join(v)),
+            DhcpOption::Pop3Server(v) => write!(f, "Pop3Server([{}])", join(v)),
+            DhcpOption::NntpServer(v) => write!(f, "NntpServer([{}])", join(v)),
+            DhcpOption::WwwServer(v) => write!(f, "WwwServer([{}])", join(v)),
+            DhcpOption::FingerServer(v) => write!(f, "FingerServer([{}])", join(v)),
+            DhcpOption::IrcServer(v) => write!(f, "IrcServer([{}])", join(v)),
+            DhcpOption::StreetTalkServer(v) => write!(f, "StreetTalkServer([{}])", join(v)),
+            DhcpOption::StreetTalkDirectoryAssistanceServer(v) => write!(f, "StreetTalkDirectoryAssistanceServer([{}])", join(v)),
+            DhcpOption::End => write!(f, "End"),
+            DhcpOption::RelayAgentInformation(v) => write!(f, "RelayAgentInformation({:?})", v),
+            DhcpOption::ClientFqdn(v) => write!(f, "ClientFqdn({:?})", v),
+            DhcpOption::ClasslessStaticRoute(v) => write!(f, "ClasslessStaticRoute({:?})", v),
+            DhcpOption::MsClasslessStaticRoute(v) => write!(f, "MsClasslessStaticRoute({:?})", v),
+            DhcpOption::TftpServerAddress(v) => write!(f, "TftpServerAddress([{}])", join(v)),
+            DhcpOption::NdsServers(v) => write!(f, "NdsServers([{}])", join(v)),
+            DhcpOption::NdsTreeName(v) => write!(f, "NdsTreeName({})", hex(v)),
+            DhcpOption::NdsContext(v) => write!(f, "NdsContext({})", hex(v)),
+            DhcpOption::PosixTimezone(v) => write!(f, "PosixTimezone({})", v),
+            DhcpOption::TimezoneDatabase(v) => write!(f, "TimezoneDatabase({})", v),
+            DhcpOption::PxeMagic(v) => write!(f, "PxeMagic({})", hex(v)),
+            DhcpOption::PxeConfigFile(v) => write!(f, "PxeConfigFile({})", v),
+            DhcpOption::PxePathPrefix(v) => write!(f, "PxePathPrefix({})", v),
+            DhcpOption::PxeRebootTime(v) => write!(f, "PxeRebootTime({})", v),
+            DhcpOption::DomainSearch(v) => write!(f, "DomainSearch([{}])", join(v)),
+            DhcpOption::UserClass(v) => write!(f, "UserClass({:?})", v),
+            DhcpOption::SipServers(v) => write!(f, "SipServers({:?})", v),
+            DhcpOption::CaptivePortal(v) => write!(f, "CaptivePortal({})", v),
+            DhcpOption::ClientArchitecture(v) => write!(f, "ClientArchitecture({:?})", v),
+            DhcpOption::ClientMachineIdentifier(v) => write!(f, "ClientMachineIdentifier({:?})", v),
+            DhcpOption::RapidCommit => write!(f, "RapidCommit"),
+            DhcpOption::Authentication(v) => write!(f, "Authentication({:?})", v),
+            DhcpOption::Unknown(tag, data) => write!(f, "Unknown({}, {})", tag, hex(data)),
+        }
+    }
+}
+
+fn duration_from_seconds(seconds: u32) -> Duration {
+    match seconds {
+        u32::MAX => INFINITE_TIME,
+        seconds => Duration::from_secs(u64::from(seconds)),
+    }
+}
+
+/// Number of leading one bits, `None` if the mask is not contiguous
+fn mask_prefix_length(mask: Ipv4Addr) -> Option<u8> {
+    let mask = u32::from(mask);
+    if mask.leading_ones() + mask.trailing_zeros() == 32 {
+        Some(mask.leading_ones() as u8)
+    } else {
+        None
+    }
+}
+
+/// Walks encapsulated options (rfc 2132 8.4 layout) as `(code, data)` pairs
+fn encapsulated_options(data: &[u8], tag: u8) -> DhcpResult<Vec<(u8, Vec<u8>)>> {
+    let mut sub_options = Vec::new();
+    let mut bytes = data;
+
+    while let Some((&code, rest)) = bytes.split_first() {
+        if code == END {
+            break;
+        }
+        if code == PAD {
+            bytes = rest;
+            continue;
+        }
+
+        let (&length, rest) = rest.split_first().ok_or(DhcpError::OptionParseError(tag))?;
+        let value = rest.get(..length as usize).ok_or(DhcpError::OptionParseError(tag))?;
+
+        sub_options.push((code, value.to_vec()));
+        bytes = &rest[length as usize..];
+    }
+
+    Ok(sub_options)
+}
+
 impl DhcpOption {
     /// Try to get value if type is known without match
     pub fn try_to_bool(&self) -> DhcpResult<bool> {
-        Ok(match self {
+        Ok(*match self {
             DhcpOption::IpForwarding(v) => v,
             DhcpOption::NonLocalSourceRouting(v) => v,
             DhcpOption::AllSubnetsLocal(v) => v,
+            DhcpOption::PerformMaskDiscovery(v) => v,
             DhcpOption::MaskSupplier(v) => v,
+            DhcpOption::PerformRouterDiscovery(v) => v,
             DhcpOption::TrailerEncapsulation(v) => v,
             DhcpOption::EthernetEncapsulation(v) => v,
             DhcpOption::TcpKeepAliveGarbage(v) => v,
             _ => return Err(DhcpError::ConversionError(self.tag()))
-        }.clone())
+        })
     }
 
     /// Try to get value if type is known without match
@@ -528,6 +1315,9 @@ impl DhcpOption {
             DhcpOption::VendorSpecific(v) => v,
             DhcpOption::ParameterRequestList(v) => v,
             DhcpOption::VendorClassIdentifier(v) => v,
+            DhcpOption::NdsTreeName(v) => v,
+            DhcpOption::NdsContext(v) => v,
+            DhcpOption::PxeMagic(v) => v,
             _ => return Err(DhcpError::ConversionError(self.tag()))
         }.clone())
     }
@@ -545,13 +1335,18 @@ impl DhcpOption {
             DhcpOption::NetworkInformationServicePlusDomain(v) => v,
             DhcpOption::TftpServer(v) => v,
             DhcpOption::BootFileName(v) => v,
+            DhcpOption::CaptivePortal(v) => v,
+            DhcpOption::PosixTimezone(v) => v,
+            DhcpOption::TimezoneDatabase(v) => v,
+            DhcpOption::PxeConfigFile(v) => v,
+            DhcpOption::PxePathPrefix(v) => v,
             _ => return Err(DhcpError::ConversionError(self.tag()))
         }.clone())
     }
 
     /// Try to get value if type is known without match
     pub fn try_to_ipv4(&self) -> DhcpResult<Ipv4Addr> {
-        Ok(match self {
+        Ok(*match self {
             DhcpOption::SubnetMask(v, ) => v,
             DhcpOption::SwapServer(v, ) => v,
             DhcpOption::BroadcastAddress(v, ) => v,
@@ -559,7 +1354,7 @@ impl DhcpOption {
             DhcpOption::RequestedIpAddress(v, ) => v,
             DhcpOption::ServerIdentifier(v, ) => v,
             _ => return Err(DhcpError::ConversionError(self.tag()))
-        }.clone())
+        })
     }
 
     /// Try to get value if type is known without match
@@ -590,6 +1385,8 @@ impl DhcpOption {
             DhcpOption::IrcServer(v, ) => v,
             DhcpOption::StreetTalkServer(v, ) => v,
             DhcpOption::StreetTalkDirectoryAssistanceServer(v, ) => v,
+            DhcpOption::TftpServerAddress(v) => v,
+            DhcpOption::NdsServers(v) => v,
             _ => return Err(DhcpError::ConversionError(self.tag()))
         }.clone())
     }
@@ -622,6 +1419,7 @@ impl DhcpOption {
             DhcpOption::IpAddressLeaseTime(v, ) => v,
             DhcpOption::RenewalTimeValue(v, ) => v,
             DhcpOption::RebindingTimeValue(v, ) => v,
+            DhcpOption::PxeRebootTime(v) => v,
             _ => return Err(DhcpError::ConversionError(self.tag()))
         })
     }
@@ -634,6 +1432,112 @@ impl DhcpOption {
         })
     }
 
+    /// Prefix length of a [`DhcpOption::SubnetMask`], e.g. 24 for `255.255.255.0`
+    ///
+    /// `None` for other options or a non-contiguous mask.
+    pub fn prefix_length(&self) -> Option<u8> {
+        match self {
+            DhcpOption::SubnetMask(mask) => mask_prefix_length(*mask),
+            _ => None
+        }
+    }
+
+    /// [`DhcpOption::IpAddressLeaseTime`] from a [`Duration`], saturating to `u32::MAX` (infinite)
+    ///
+    /// Durations below one second cannot be represented and fail.
+    pub fn lease_time_from_duration(duration: Duration) -> DhcpResult<DhcpOption> {
+        Self::seconds_from_duration(duration, IP_ADDRESS_LEASE_TIME).map(DhcpOption::IpAddressLeaseTime)
+    }
+
+    /// [`DhcpOption::RenewalTimeValue`] (T1) from a [`Duration`], see [`DhcpOption::lease_time_from_duration`]
+    pub fn renewal_time_from_duration(duration: Duration) -> DhcpResult<DhcpOption> {
+        Self::seconds_from_duration(duration, RENEWAL_TIME_VALUE).map(DhcpOption::RenewalTimeValue)
+    }
+
+    /// [`DhcpOption::RebindingTimeValue`] (T2) from a [`Duration`], see [`DhcpOption::lease_time_from_duration`]
+    pub fn rebinding_time_from_duration(duration: Duration) -> DhcpResult<DhcpOption> {
+        Self::seconds_from_duration(duration, REBINDING_TIME_VALUE).map(DhcpOption::RebindingTimeValue)
+    }
+
+    fn seconds_from_duration(duration: Duration, tag: u8) -> DhcpResult<u32> {
+        match duration.as_secs() {
+            0 => Err(DhcpError::OptionInvalidValueError(tag)),
+            seconds => Ok(u32::try_from(seconds).unwrap_or(u32::MAX)),
+        }
+    }
+
+    /// Converts a [`DhcpOption::ClasslessStaticRoute`] into a legacy [`DhcpOption::StaticRoute`]
+    ///
+    /// Fails if a route is not a host route (/32) since option 33 cannot represent the prefix.
+    pub fn classful_from_classless(&self) -> DhcpResult<DhcpOption> {
+        match self {
+            DhcpOption::ClasslessStaticRoute(routes) => {
+                routes.iter().map(|r| {
+                    if r.prefix_length == 32 {
+                        Ok(StaticRoute {
+                            destination: r.destination,
+                            router: r.router,
+                        })
+                    } else {
+                        Err(DhcpError::ConversionError(self.tag()))
+                    }
+                }).collect::<DhcpResult<Vec<StaticRoute>>>().map(DhcpOption::StaticRoute)
+            }
+            _ => Err(DhcpError::ConversionError(self.tag()))
+        }
+    }
+
+    /// Whether a route option contains the same destination with different routers
+    ///
+    /// Always false for options not carrying routes.
+    pub fn has_conflicting_routes(&self) -> bool {
+        let mut routes: HashMap<(Ipv4Addr, u8), Ipv4Addr> = HashMap::new();
+        let mut conflicts = |destination, router| {
+            *routes.entry(destination).or_insert(router) != router
+        };
+
+        match self {
+            DhcpOption::StaticRoute(r) => r.iter()
+                .any(|r| conflicts((r.destination, 32), r.router)),
+            DhcpOption::ClasslessStaticRoute(r) | DhcpOption::MsClasslessStaticRoute(r) => r.iter()
+                .any(|r| conflicts((r.destination, r.prefix_length), r.router)),
+            _ => false
+        }
+    }
+
+    /// Parses the iPXE feature sub-options carried in `Unknown(175, ..)` as `(code, data)` pairs
+    pub fn ipxe_features(&self) -> DhcpResult<Vec<(u8, Vec<u8>)>> {
+        match self {
+            DhcpOption::Unknown(IPXE_ENCAPSULATED, data) => encapsulated_options(data, IPXE_ENCAPSULATED),
+            _ => Err(DhcpError::ConversionError(self.tag()))
+        }
+    }
+
+    /// Parses the payload of [`DhcpOption::VendorSpecific`] as `(code, data)` sub-options
+    ///
+    /// Parsing stops at an end marker (255), pad bytes are skipped.
+    pub fn vendor_specific_suboptions(&self) -> DhcpResult<Vec<(u8, Vec<u8>)>> {
+        match self {
+            DhcpOption::VendorSpecific(data) => encapsulated_options(data, VENDOR_SPECIFIC),
+            _ => Err(DhcpError::ConversionError(self.tag()))
+        }
+    }
+
+    /// Builds a [`DhcpOption::VendorSpecific`] from `(code, data)` sub-options
+    ///
+    /// Fails if a sub-option holds more than 255 bytes.
+    pub fn vendor_specific_from_suboptions(sub_options: Vec<(u8, Vec<u8>)>) -> DhcpResult<DhcpOption> {
+        let mut data = vec![];
+        for (code, value) in sub_options {
+            let length = u8::try_from(value.len()).map_err(|_| DhcpError::OptionInvalidValueError(VENDOR_SPECIFIC))?;
+            data.push(code);
+            data.push(length);
+            data.extend(value);
+        }
+
+        Ok(DhcpOption::VendorSpecific(data))
+    }
+
     /// Returns the Dhcp tag
     pub fn tag(&self) -> u8 {
         match self {
@@ -666,6 +1570,7 @@ impl DhcpOption {
             DhcpOption::InterfaceMtu(_) => INTERFACE_MTU,
             DhcpOption::AllSubnetsLocal(_) => ALL_SUBNETS_LOCAL,
             DhcpOption::BroadcastAddress(_) => BROADCAST_ADDRESS,
+            DhcpOption::PerformMaskDiscovery(_) => PERFORM_MASK_DISCOVERY,
             DhcpOption::MaskSupplier(_) => MASK_SUPPLIER,
             DhcpOption::PerformRouterDiscovery(_) => PERFORM_ROUTER_DISCOVERY,
             DhcpOption::RouterSolicitationAddress(_) => ROUTER_SOLICITATION_ADDRESS,
@@ -677,7 +1582,7 @@ impl DhcpOption {
             DhcpOption::TcpKeepAliveInterval(_) => TCP_KEEPALIVE_INTERVAL,
             DhcpOption::TcpKeepAliveGarbage(_) => TCP_KEEPALIVE_GARGABE,
             DhcpOption::NetworkInformationServiceDomain(_) => NETWORK_INFORMATION_SERVICE_DOMAIN,
-            DhcpOption::NetworkInformationServers(_) => NETBIOS_OVER_TCP_IP_NAME_SERVER,
+            DhcpOption::NetworkInformationServers(_) => NETWORK_INFORMATION_SERVERS,
             DhcpOption::NetworkTimeProtocolServers(_) => NETWORK_TIME_PROTOCOL_SERVERS,
             DhcpOption::VendorSpecific(_) => VENDOR_SPECIFIC,
             DhcpOption::NetBiosOverTcpIpNameServer(_) => NETBIOS_OVER_TCP_IP_NAME_SERVER,
@@ -713,6 +1618,27 @@ impl DhcpOption {
             DhcpOption::StreetTalkDirectoryAssistanceServer(_) => STREET_TALK_DIRECTORY_ASSISTANCE,
             DhcpOption::End => END,
             DhcpOption::RelayAgentInformation(_) => RELAY_AGENT_INFORMATION,
+            DhcpOption::ClientFqdn(_) => CLIENT_FQDN,
+            DhcpOption::ClasslessStaticRoute(_) => CLASSLESS_STATIC_ROUTE,
+            DhcpOption::MsClasslessStaticRoute(_) => MS_CLASSLESS_STATIC_ROUTE,
+            DhcpOption::TftpServerAddress(_) => TFTP_SERVER_ADDRESS,
+            DhcpOption::NdsServers(_) => NDS_SERVERS,
+            DhcpOption::NdsTreeName(_) => NDS_TREE_NAME,
+            DhcpOption::NdsContext(_) => NDS_CONTEXT,
+            DhcpOption::PosixTimezone(_) => POSIX_TIMEZONE,
+            DhcpOption::TimezoneDatabase(_) => TIMEZONE_DATABASE,
+            DhcpOption::PxeMagic(_) => PXE_MAGIC,
+            DhcpOption::PxeConfigFile(_) => PXE_CONFIG_FILE,
+            DhcpOption::PxePathPrefix(_) => PXE_PATH_PREFIX,
+            DhcpOption::PxeRebootTime(_) => PXE_REBOOT_TIME,
+            DhcpOption::DomainSearch(_) => DOMAIN_SEARCH,
+            DhcpOption::UserClass(_) => USER_CLASS,
+            DhcpOption::SipServers(_) => SIP_SERVERS,
+            DhcpOption::CaptivePortal(_) => CAPTIVE_PORTAL,
+            DhcpOption::ClientArchitecture(_) => CLIENT_ARCHITECTURE,
+            DhcpOption::ClientMachineIdentifier(_) => CLIENT_MACHINE_IDENTIFIER,
+            DhcpOption::RapidCommit => RAPID_COMMIT,
+            DhcpOption::Authentication(_) => AUTHENTICATION,
             DhcpOption::Unknown(tag, _) => *tag,
         }
     }
@@ -721,7 +1647,11 @@ impl DhcpOption {
     pub fn from_bytes(tag: u8, _length: usize, data: &[u8]) -> DhcpResult<Self> {
         Ok(match tag {
             PAD => Self::Pad,
-            SUBNET_MASK => Self::SubnetMask(data.try_from_option(tag)?),
+            SUBNET_MASK => {
+                let mask = data.try_from_option(tag)?;
+                mask_prefix_length(mask).ok_or(DhcpError::OptionInvalidValueError(tag))?;
+                Self::SubnetMask(mask)
+            }
             TIME_OFFSET => Self::TimeOffset(data.try_from_option(tag)?),
             ROUTER => Self::Router(data.try_from_option_min_bytes(tag, 4)?),
             TIME_SERVER => Self::TimeServer(data.try_from_option_min_bytes(tag, 4)?),
@@ -755,7 +1685,7 @@ impl DhcpOption {
             INTERFACE_MTU => Self::InterfaceMtu(data.try_from_option(tag)?),
             ALL_SUBNETS_LOCAL => Self::AllSubnetsLocal(data.try_from_option(tag)?),
             BROADCAST_ADDRESS => Self::BroadcastAddress(data.try_from_option(tag)?),
-            PERFORM_MASK_DISCOVERY => Self::PerformRouterDiscovery(data.try_from_option(tag)?),
+            PERFORM_MASK_DISCOVERY => Self::PerformMaskDiscovery(data.try_from_option(tag)?),
             MASK_SUPPLIER => Self::MaskSupplier(data.try_from_option(tag)?),
             PERFORM_ROUTER_DISCOVERY => Self::PerformRouterDiscovery(data.try_from_option(tag)?),
             ROUTER_SOLICITATION_ADDRESS => Self::RouterSolicitationAddress(data.try_from_option_min_bytes(tag, 4)?),
@@ -800,9 +1730,10 @@ impl DhcpOption {
             REBINDING_TIME_VALUE => Self::RebindingTimeValue(data.try_from_option(tag)?),
             VENDOR_CLASS_IDENTIFIER => Self::VendorClassIdentifier(data.try_from_option_min_bytes(tag, 1)?),
             CLIENT_IDENTIFIER => {
+                let (typ, data) = data.split_first().ok_or(DhcpError::OptionParseError(tag))?;
                 Self::ClientIdentifier(ClientIdentifier {
-                    typ: data[0],
-                    data: data[1..].to_vec(),
+                    typ: *typ,
+                    data: data.to_vec(),
                 })
             }
             NETWORK_INFORMATION_SERVICE_PLUS_DOMAIN => Self::NetworkInformationServicePlusDomain(data.try_from_option_min_bytes(tag, 4)?),
@@ -820,101 +1751,1046 @@ impl DhcpOption {
             STREET_TALK_DIRECTORY_ASSISTANCE => Self::StreetTalkDirectoryAssistanceServer(data.try_from_option_min_bytes(tag, 4)?),
             END => Self::End,
             RELAY_AGENT_INFORMATION => Self::RelayAgentInformation(data.try_from_option(tag)?),
+            CLIENT_FQDN => Self::ClientFqdn(data.try_from_option_min_bytes(tag, 3)?),
+            CLASSLESS_STATIC_ROUTE => Self::ClasslessStaticRoute(data.try_from_option_min_bytes(tag, 5)?),
+            MS_CLASSLESS_STATIC_ROUTE => Self::MsClasslessStaticRoute(data.try_from_option_min_bytes(tag, 5)?),
+            TFTP_SERVER_ADDRESS => Self::TftpServerAddress(data.try_from_option_min_bytes(tag, 4)?),
+            NDS_SERVERS => Self::NdsServers(data.try_from_option_min_bytes(tag, 4)?),
+            NDS_TREE_NAME => Self::NdsTreeName(data.try_from_option_min_bytes(tag, 1)?),
+            NDS_CONTEXT => Self::NdsContext(data.try_from_option_min_bytes(tag, 1)?),
+            POSIX_TIMEZONE => Self::PosixTimezone(data.try_from_option_min_bytes(tag, 1)?),
+            TIMEZONE_DATABASE => Self::TimezoneDatabase(data.try_from_option_min_bytes(tag, 1)?),
+            PXE_MAGIC => Self::PxeMagic(data.try_from_option_min_bytes(tag, 4)?),
+            PXE_CONFIG_FILE => Self::PxeConfigFile(data.try_from_option_min_bytes(tag, 1)?),
+            PXE_PATH_PREFIX => Self::PxePathPrefix(data.try_from_option_min_bytes(tag, 1)?),
+            PXE_REBOOT_TIME => Self::PxeRebootTime(data.try_from_option(tag)?),
+            DOMAIN_SEARCH => Self::DomainSearch(data.try_from_option_min_bytes(tag, 1)?),
+            USER_CLASS => Self::UserClass(data.try_from_option_min_bytes(tag, 2)?),
+            SIP_SERVERS => Self::SipServers(data.try_from_option_min_bytes(tag, 2)?),
+            CAPTIVE_PORTAL => Self::CaptivePortal(data.try_from_option_min_bytes(tag, 1)?),
+            CLIENT_ARCHITECTURE => Self::ClientArchitecture(data.try_from_option_min_bytes(tag, 2)?),
+            CLIENT_MACHINE_IDENTIFIER => Self::ClientMachineIdentifier(data.try_from_option(tag)?),
+            RAPID_COMMIT if data.is_empty() => Self::RapidCommit,
+            RAPID_COMMIT => return Err(DhcpError::OptionParseError(tag)),
+            AUTHENTICATION => Self::Authentication(data.try_from_option(tag)?),
             _ => Self::Unknown(tag, data.to_vec())
         })
     }
 
+    /// Same as [`DhcpOption::from_bytes`] using the given parser configuration
+    pub fn from_bytes_with(tag: u8, length: usize, data: &[u8], parse_options: &ParseOptions) -> DhcpResult<Self> {
+        let option = Self::from_bytes(tag, length, data)?;
+
+        if let (Some(max), Ok(value)) = (parse_options.max_string_len, option.try_to_ascii()) {
+            if value.len() > max {
+                return Err(DhcpError::OptionInvalidValueError(tag));
+            }
+        }
+
+        Ok(option)
+    }
+
+    /// Number of bytes [`DhcpOption::to_bytes`] produces, including tag and length
+    pub fn encoded_len(&self) -> usize {
+        self.with_data(|_, data| data.option_len()).unwrap_or(1)
+    }
+
+    /// Same as [`DhcpOption::to_bytes`] but fails if the data does not fit a single option
+    ///
+    /// [`DhcpOption::VendorSpecific`], [`DhcpOption::DomainSearch`] and [`DhcpOption::SipServers`] never
+    /// fail, they are split into multiple options instead (rfc 3396). Domain names with empty labels or
+    /// labels longer than 63 bytes fail as well, [`DhcpOption::to_bytes`] leaves them out.
+    pub fn try_to_bytes(&self) -> DhcpResult<Vec<u8>> {
+        if let DhcpOption::DomainSearch(names) | DhcpOption::SipServers(SipServers::DomainNames(names)) = self {
+            check_dns_names(names, self.tag())?;
+        }
+
+        let bytes = self.to_bytes();
+        let split = matches!(self, DhcpOption::VendorSpecific(_) | DhcpOption::DomainSearch(_) | DhcpOption::SipServers(_));
+        if !split && bytes.len() > 2 + u8::MAX as usize {
+            return Err(DhcpError::OptionInvalidValueError(self.tag()));
+        }
+
+        Ok(bytes)
+    }
+
     /// Generates a new list of bytes with tag and content
+    ///
+    /// Data exceeding 255 bytes is split into multiple options of the same tag (rfc 3396).
     pub fn to_bytes(&self) -> Vec<u8> {
-        match self {
-            DhcpOption::Pad => vec![PAD],
-            DhcpOption::SubnetMask(data) => data.to_option_bytes(SUBNET_MASK),
-            DhcpOption::TimeOffset(data) => data.to_option_bytes(TIME_OFFSET),
-            DhcpOption::Router(data) => data.to_option_bytes(ROUTER),
-            DhcpOption::TimeServer(data) => data.to_option_bytes(TIME_SERVER),
-            DhcpOption::NameServer(data) => data.to_option_bytes(NAME_SERVER),
-            DhcpOption::DomainNameServer(data) => data.to_option_bytes(DOMAIN_NAME_SERVER),
-            DhcpOption::LogServer(data) => data.to_option_bytes(LOG_SERVER),
-            DhcpOption::CookieServer(data) => data.to_option_bytes(COOKIE_SERVER),
-            DhcpOption::LPRServer(data) => data.to_option_bytes(LPR_SERVER),
-            DhcpOption::ImpressServer(data) => data.to_option_bytes(IMPRESS_SERVER),
-            DhcpOption::ResourceLocationServer(data) => data.to_option_bytes(RESOURCE_LOCATION_SERVER),
-            DhcpOption::HostName(data) => data.to_option_bytes(HOST_NAME),
-            DhcpOption::BootFileSize(data) => data.to_option_bytes(BOOT_FILE_SIZE),
-            DhcpOption::MeritDumpFile(data) => data.to_option_bytes(MERIT_DUMP_FILE),
-            DhcpOption::DomainName(data) => data.to_option_bytes(DOMAIN_NAME),
-            DhcpOption::SwapServer(data) => data.to_option_bytes(SWAP_SERVER),
-            DhcpOption::RootPath(data) => data.to_option_bytes(ROOT_PATH),
-            DhcpOption::ExtensionPath(data) => data.to_option_bytes(EXTENSION_PATH),
-            DhcpOption::IpForwarding(data) => data.to_option_bytes(IP_FORWARDING),
-            DhcpOption::NonLocalSourceRouting(data) => data.to_option_bytes(NON_LOCAL_SOURCE_ROUTING),
-            DhcpOption::PolicyFilter(data) => data.to_option_bytes(POLICY_FILTER),
-            DhcpOption::MaximumDatagramReassemblySize(data) => data.to_option_bytes(MAXIMUM_DATAGRAM_REASSEMBLY_SIZE),
-            DhcpOption::DefaultIpTTL(data) => data.to_option_bytes(DEFAULT_IP_TTL),
-            DhcpOption::PathMtuAgingTimeout(data) => data.to_option_bytes(PATH_MTU_AGING_TIMEOUT),
-            DhcpOption::PathMtuPlateauTable(data) => data.to_option_bytes(PATH_MTU_PLATEAU_TABLE),
-            DhcpOption::InterfaceMtu(data) => data.to_option_bytes(INTERFACE_MTU),
-            DhcpOption::AllSubnetsLocal(data) => data.to_option_bytes(ALL_SUBNETS_LOCAL),
-            DhcpOption::BroadcastAddress(data) => data.to_option_bytes(BROADCAST_ADDRESS),
-            DhcpOption::MaskSupplier(data) => data.to_option_bytes(MASK_SUPPLIER),
-            DhcpOption::PerformRouterDiscovery(data) => data.to_option_bytes(PERFORM_ROUTER_DISCOVERY),
-            DhcpOption::RouterSolicitationAddress(data) => data.to_option_bytes(ROUTER_SOLICITATION_ADDRESS),
-            DhcpOption::StaticRoute(data) => data.to_option_bytes(STATIC_ROUTE),
-            DhcpOption::TrailerEncapsulation(data) => data.to_option_bytes(TRAILER_ENCAPSULATION),
-            DhcpOption::ArpCacheTimeout(data) => data.to_option_bytes(ARP_CACHE_TIMEOUT),
-            DhcpOption::EthernetEncapsulation(data) => data.to_option_bytes(ETHERNET_ENCAPSULATION),
-            DhcpOption::TcpDefaultTTL(data) => data.to_option_bytes(TCP_DEFAULT_TTL),
-            DhcpOption::TcpKeepAliveInterval(data) => data.to_option_bytes(TCP_KEEPALIVE_INTERVAL),
-            DhcpOption::TcpKeepAliveGarbage(data) => data.to_option_bytes(TCP_KEEPALIVE_GARGABE),
-            DhcpOption::NetworkInformationServiceDomain(data) => data.to_option_bytes(NETWORK_INFORMATION_SERVICE_DOMAIN),
-            DhcpOption::NetworkInformationServers(data) => data.to_option_bytes(NETWORK_INFORMATION_SERVERS),
-            DhcpOption::NetworkTimeProtocolServers(data) => data.to_option_bytes(NETWORK_TIME_PROTOCOL_SERVERS),
-            DhcpOption::VendorSpecific(data) => data.to_option_bytes(VENDOR_SPECIFIC),
-            DhcpOption::NetBiosOverTcpIpNameServer(data) => data.to_option_bytes(NETBIOS_OVER_TCP_IP_NAME_SERVER),
-            DhcpOption::NetBiosOverTcpIpDatagramDistributionServer(data) => data.to_option_bytes(NETBIOS_OVER_TCP_IP_DATAGRAM_DISTRIBUTION_SERVER),
-            DhcpOption::NetBiosOverTcpIpNodeType(data) => data.to_option_bytes(NETBIOS_OVER_TCP_IP_NODE_TYPE),
-            DhcpOption::NetBiosOverTcpIpScope(data) => data.to_option_bytes(NETBIOS_OVER_TCP_IP_SCOPE),
-            DhcpOption::XWindowSystemFontServer(data) => data.to_option_bytes(X_WINDOW_SYSTEM_FONT_SERVER),
-            DhcpOption::XWindowSystemDisplayManager(data) => data.to_option_bytes(X_WINDOW_SYSTEM_DISPLAY_MANAGER),
-            DhcpOption::RequestedIpAddress(data) => data.to_option_bytes(REQUESTED_IP_ADDRESS),
-            DhcpOption::IpAddressLeaseTime(data) => data.to_option_bytes(IP_ADDRESS_LEASE_TIME),
-            DhcpOption::OptionOverload(data) => data.to_option_bytes(OPTION_OVERLOAD),
-            DhcpOption::MessageType(data) => data.to_option_bytes(MESSAGE_TYPE),
-            DhcpOption::ServerIdentifier(data) => data.to_option_bytes(SERVER_IDENTIFIER),
-            DhcpOption::ParameterRequestList(data) => data.to_option_bytes(PARAMETER_REQUEST_LIST),
-            DhcpOption::Message(data) => data.to_option_bytes(MESSAGE),
-            DhcpOption::MaximumDhcpMessageSize(data) => data.to_option_bytes(MAXIMUM_DHCP_MESSAGE_SIZE),
-            DhcpOption::RenewalTimeValue(data) => data.to_option_bytes(RENEWAL_TIME_VALUE),
-            DhcpOption::RebindingTimeValue(data) => data.to_option_bytes(REBINDING_TIME_VALUE),
-            DhcpOption::VendorClassIdentifier(data) => data.to_option_bytes(VENDOR_CLASS_IDENTIFIER),
-            DhcpOption::ClientIdentifier(client_identifier) => {
-                let mut bytes = client_identifier.data.clone();
-                bytes.insert(0, bytes.len() as u8);
-                bytes.insert(0, client_identifier.typ);
-                bytes.insert(0, CLIENT_IDENTIFIER);
-                bytes
-            }
-            DhcpOption::NetworkInformationServicePlusDomain(data) => data.to_option_bytes(NETWORK_INFORMATION_SERVICE_PLUS_DOMAIN),
-            DhcpOption::NetworkInformationServicePlusServer(data) => data.to_option_bytes(NETWORK_INFORMATION_SERVICE_PLUS_SERVERS),
-            DhcpOption::TftpServer(data) => data.to_option_bytes(TFTP_SERVER_NAME),
-            DhcpOption::BootFileName(data) => data.to_option_bytes(BOOT_FILE_NAME),
-            DhcpOption::MobileIpHomeAgent(data) => data.to_option_bytes(MOBILE_IP_HOME_AGENT),
-            DhcpOption::SmtpServer(data) => data.to_option_bytes(SMTP_SERVER),
-            DhcpOption::Pop3Server(data) => data.to_option_bytes(POP3_SERVER),
-            DhcpOption::NntpServer(data) => data.to_option_bytes(NNTP_SERVER),
-            DhcpOption::WwwServer(data) => data.to_option_bytes(WWW_SERVER),
-            DhcpOption::FingerServer(data) => data.to_option_bytes(FINGER_SERVER),
-            DhcpOption::IrcServer(data) => data.to_option_bytes(IRC_SERVER),
-            DhcpOption::StreetTalkServer(data) => data.to_option_bytes(STREET_TALK_SERVER),
-            DhcpOption::StreetTalkDirectoryAssistanceServer(data) => data.to_option_bytes(STREET_TALK_DIRECTORY_ASSISTANCE),
-            DhcpOption::End => vec![END],
-            DhcpOption::RelayAgentInformation(data) => data.to_option_bytes(RELAY_AGENT_INFORMATION),
-            DhcpOption::Unknown(tag, data) => {
-                let mut bytes = data.clone();
-                bytes.insert(0, bytes.len() as u8);
-                bytes.insert(0, *tag);
-                bytes
-            }
+        let mut bytes = vec![];
+        self.write_to(&mut bytes);
+        bytes
+    }
+
+    /// Appends the bytes of [`DhcpOption::to_bytes`] to `buf`
+    pub fn write_to(&self, buf: &mut Vec<u8>) {
+        if self.with_data(|tag, data| data.write_option(tag, buf)).is_none() {
+            buf.push(self.tag());
         }
     }
+
+    /// Calls `f` with tag and value of the option, `None` for `Pad` and `End` which have no length byte
+    fn with_data<R>(&self, f: impl FnOnce(u8, &dyn ToOptionBytes) -> R) -> Option<R> {
+        Some(match self {
+            DhcpOption::Pad => return None,
+            DhcpOption::SubnetMask(data) => f(SUBNET_MASK, data),
+            DhcpOption::TimeOffset(data) => f(TIME_OFFSET, data),
+            DhcpOption::Router(data) => f(ROUTER, data),
+            DhcpOption::TimeServer(data) => f(TIME_SERVER, data),
+            DhcpOption::NameServer(data) => f(NAME_SERVER, data),
+            DhcpOption::DomainNameServer(data) => f(DOMAIN_NAME_SERVER, data),
+            DhcpOption::LogServer(data) => f(LOG_SERVER, data),
+            DhcpOption::CookieServer(data) => f(COOKIE_SERVER, data),
+            DhcpOption::LPRServer(data) => f(LPR_SERVER, data),
+            DhcpOption::ImpressServer(data) => f(IMPRESS_SERVER, data),
+            DhcpOption::ResourceLocationServer(data) => f(RESOURCE_LOCATION_SERVER, data),
+            DhcpOption::HostName(data) => f(HOST_NAME, data),
+            DhcpOption::BootFileSize(data) => f(BOOT_FILE_SIZE, data),
+            DhcpOption::MeritDumpFile(data) => f(MERIT_DUMP_FILE, data),
+            DhcpOption::DomainName(data) => f(DOMAIN_NAME, data),
+            DhcpOption::SwapServer(data) => f(SWAP_SERVER, data),
+            DhcpOption::RootPath(data) => f(ROOT_PATH, data),
+            DhcpOption::ExtensionPath(data) => f(EXTENSION_PATH, data),
+            DhcpOption::IpForwarding(data) => f(IP_FORWARDING, &data),
+            DhcpOption::NonLocalSourceRouting(data) => f(NON_LOCAL_SOURCE_ROUTING, &data),
+            DhcpOption::PolicyFilter(data) => f(POLICY_FILTER, &data),
+            DhcpOption::MaximumDatagramReassemblySize(data) => f(MAXIMUM_DATAGRAM_REASSEMBLY_SIZE, data),
+            DhcpOption::DefaultIpTTL(data) => f(DEFAULT_IP_TTL, &data),
+            DhcpOption::PathMtuAgingTimeout(data) => f(PATH_MTU_AGING_TIMEOUT, data),
+            DhcpOption::PathMtuPlateauTable(data) => f(PATH_MTU_PLATEAU_TABLE, &data),
+            DhcpOption::InterfaceMtu(data) => f(INTERFACE_MTU, data),
+            DhcpOption::AllSubnetsLocal(data) => f(ALL_SUBNETS_LOCAL, &data),
+            DhcpOption::BroadcastAddress(data) => f(BROADCAST_ADDRESS, data),
+            DhcpOption::PerformMaskDiscovery(data) => f(PERFORM_MASK_DISCOVERY, &data),
+            DhcpOption::MaskSupplier(data) => f(MASK_SUPPLIER, &data),
+            DhcpOption::PerformRouterDiscovery(data) => f(PERFORM_ROUTER_DISCOVERY, &data),
+            DhcpOption::RouterSolicitationAddress(data) => f(ROUTER_SOLICITATION_ADDRESS, data),
+            DhcpOption::StaticRoute(data) => f(STATIC_ROUTE, &data),
+            DhcpOption::TrailerEncapsulation(data) => f(TRAILER_ENCAPSULATION, &data),
+            DhcpOption::ArpCacheTimeout(data) => f(ARP_CACHE_TIMEOUT, data),
+            DhcpOption::EthernetEncapsulation(data) => f(ETHERNET_ENCAPSULATION, &data),
+            DhcpOption::TcpDefaultTTL(data) => f(TCP_DEFAULT_TTL, &data),
+            DhcpOption::TcpKeepAliveInterval(data) => f(TCP_KEEPALIVE_INTERVAL, data),
+            DhcpOption::TcpKeepAliveGarbage(data) => f(TCP_KEEPALIVE_GARGABE, &data),
+            DhcpOption::NetworkInformationServiceDomain(data) => f(NETWORK_INFORMATION_SERVICE_DOMAIN, data),
+            DhcpOption::NetworkInformationServers(data) => f(NETWORK_INFORMATION_SERVERS, data),
+            DhcpOption::NetworkTimeProtocolServers(data) => f(NETWORK_TIME_PROTOCOL_SERVERS, data),
+            DhcpOption::VendorSpecific(data) => f(VENDOR_SPECIFIC, &data),
+            DhcpOption::NetBiosOverTcpIpNameServer(data) => f(NETBIOS_OVER_TCP_IP_NAME_SERVER, data),
+            DhcpOption::NetBiosOverTcpIpDatagramDistributionServer(data) => f(NETBIOS_OVER_TCP_IP_DATAGRAM_DISTRIBUTION_SERVER, data),
+            DhcpOption::NetBiosOverTcpIpNodeType(data) => f(NETBIOS_OVER_TCP_IP_NODE_TYPE, &data),
+            DhcpOption::NetBiosOverTcpIpScope(data) => f(NETBIOS_OVER_TCP_IP_SCOPE, data),
+            DhcpOption::XWindowSystemFontServer(data) => f(X_WINDOW_SYSTEM_FONT_SERVER, data),
+            DhcpOption::XWindowSystemDisplayManager(data) => f(X_WINDOW_SYSTEM_DISPLAY_MANAGER, data),
+            DhcpOption::RequestedIpAddress(data) => f(REQUESTED_IP_ADDRESS, data),
+            DhcpOption::IpAddressLeaseTime(data) => f(IP_ADDRESS_LEASE_TIME, data),
+            DhcpOption::OptionOverload(data) => f(OPTION_OVERLOAD, &data),
+            DhcpOption::MessageType(data) => f(MESSAGE_TYPE, &data),
+            DhcpOption::ServerIdentifier(data) => f(SERVER_IDENTIFIER, data),
+            DhcpOption::ParameterRequestList(data) => f(PARAMETER_REQUEST_LIST, &data),
+            DhcpOption::Message(data) => f(MESSAGE, data),
+            DhcpOption::MaximumDhcpMessageSize(data) => f(MAXIMUM_DHCP_MESSAGE_SIZE, data),
+            DhcpOption::RenewalTimeValue(data) => f(RENEWAL_TIME_VALUE, data),
+            DhcpOption::RebindingTimeValue(data) => f(REBINDING_TIME_VALUE, data),
+            DhcpOption::VendorClassIdentifier(data) => f(VENDOR_CLASS_IDENTIFIER, &data),
+            DhcpOption::ClientIdentifier(data) => f(CLIENT_IDENTIFIER, &data),
+            DhcpOption::NetworkInformationServicePlusDomain(data) => f(NETWORK_INFORMATION_SERVICE_PLUS_DOMAIN, data),
+            DhcpOption::NetworkInformationServicePlusServer(data) => f(NETWORK_INFORMATION_SERVICE_PLUS_SERVERS, data),
+            DhcpOption::TftpServer(data) => f(TFTP_SERVER_NAME, data),
+            DhcpOption::BootFileName(data) => f(BOOT_FILE_NAME, data),
+            DhcpOption::MobileIpHomeAgent(data) => f(MOBILE_IP_HOME_AGENT, data),
+            DhcpOption::SmtpServer(data) => f(SMTP_SERVER, data),
+            DhcpOption::Pop3Server(data) => f(POP3_SERVER, data),
+            DhcpOption::NntpServer(data) => f(NNTP_SERVER, data),
+            DhcpOption::WwwServer(data) => f(WWW_SERVER, data),
+            DhcpOption::FingerServer(data) => f(FINGER_SERVER, data),
+            DhcpOption::IrcServer(data) => f(IRC_SERVER, data),
+            DhcpOption::StreetTalkServer(data) => f(STREET_TALK_SERVER, data),
+            DhcpOption::StreetTalkDirectoryAssistanceServer(data) => f(STREET_TALK_DIRECTORY_ASSISTANCE, data),
+            DhcpOption::End => return None,
+            DhcpOption::RelayAgentInformation(data) => f(RELAY_AGENT_INFORMATION, &data),
+            DhcpOption::ClientFqdn(data) => f(CLIENT_FQDN, &data),
+            DhcpOption::ClasslessStaticRoute(data) => f(CLASSLESS_STATIC_ROUTE, &data),
+            DhcpOption::MsClasslessStaticRoute(data) => f(MS_CLASSLESS_STATIC_ROUTE, &data),
+            DhcpOption::TftpServerAddress(data) => f(TFTP_SERVER_ADDRESS, data),
+            DhcpOption::NdsServers(data) => f(NDS_SERVERS, data),
+            DhcpOption::NdsTreeName(data) => f(NDS_TREE_NAME, &data),
+            DhcpOption::NdsContext(data) => f(NDS_CONTEXT, &data),
+            DhcpOption::PosixTimezone(data) => f(POSIX_TIMEZONE, data),
+            DhcpOption::TimezoneDatabase(data) => f(TIMEZONE_DATABASE, data),
+            DhcpOption::PxeMagic(data) => f(PXE_MAGIC, &data),
+            DhcpOption::PxeConfigFile(data) => f(PXE_CONFIG_FILE, data),
+            DhcpOption::PxePathPrefix(data) => f(PXE_PATH_PREFIX, data),
+            DhcpOption::PxeRebootTime(data) => f(PXE_REBOOT_TIME, data),
+            DhcpOption::DomainSearch(data) => f(DOMAIN_SEARCH, &data),
+            DhcpOption::UserClass(data) => f(USER_CLASS, &data),
+            DhcpOption::SipServers(data) => f(SIP_SERVERS, &data),
+            DhcpOption::CaptivePortal(data) => f(CAPTIVE_PORTAL, data),
+            DhcpOption::ClientArchitecture(data) => f(CLIENT_ARCHITECTURE, &data),
+            DhcpOption::ClientMachineIdentifier(data) => f(CLIENT_MACHINE_IDENTIFIER, &data),
+            DhcpOption::RapidCommit => f(RAPID_COMMIT, &()),
+            DhcpOption::Authentication(data) => f(AUTHENTICATION, &data),
+            DhcpOption::Unknown(tag, data) => f(*tag, &data),
+        })
+    }
+}
+
+#[test]
+fn test_update_domain_name_server() {
+    let mut options: DhcpOptions = vec![
+        DhcpOption::DomainNameServer(vec![Ipv4Addr::new(1, 1, 1, 1)])
+    ].into();
+
+    let updated = options.update(DOMAIN_NAME_SERVER, |o| {
+        if let DhcpOption::DomainNameServer(servers) = o {
+            servers.push(Ipv4Addr::new(8, 8, 8, 8));
+        }
+    });
+
+    assert!(updated);
+    assert_eq!(options.option(DOMAIN_NAME_SERVER),
+               Some(&DhcpOption::DomainNameServer(vec![Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(8, 8, 8, 8)])));
+    assert!(!options.update(ROUTER, |_| {}));
+}
+
+#[test]
+fn test_client_fqdn_flags() {
+    let fqdn = ClientFqdn::new(FQDN_FLAG_S | FQDN_FLAG_E, 0, 0, vec![]);
+    assert!(fqdn.server_update_requested());
+    assert!(fqdn.canonical_encoding());
+    assert!(!fqdn.server_override());
+    assert!(!fqdn.no_server_update());
+
+    let fqdn = ClientFqdn::new(FQDN_FLAG_N | FQDN_FLAG_O, 0, 0, vec![]);
+    assert!(!fqdn.server_update_requested());
+    assert!(!fqdn.canonical_encoding());
+    assert!(fqdn.server_override());
+    assert!(fqdn.no_server_update());
+}
+
+#[test]
+fn test_client_fqdn_round_trip() {
+    let bytes: &[u8] = &[CLIENT_FQDN, 7, FQDN_FLAG_S, 255, 255, b'h', b'o', b's', b't'];
+    let option = DhcpOption::from_bytes(CLIENT_FQDN, 7, &bytes[2..]).unwrap();
+    assert_eq!(option, DhcpOption::ClientFqdn(ClientFqdn::new(FQDN_FLAG_S, 255, 255, b"host".to_vec())));
+    assert_eq!(option.to_bytes(), bytes);
+}
+
+#[test]
+fn test_find_vendor_class_identifier() {
+    let options: DhcpOptions = vec![
+        DhcpOption::MessageType(MessageType::Discover),
+        DhcpOption::VendorClassIdentifier(b"blocked".to_vec()),
+    ].into();
+
+    let blocked = |o: &DhcpOption| o == &DhcpOption::VendorClassIdentifier(b"blocked".to_vec());
+    assert_eq!(options.find(blocked), options.option(VENDOR_CLASS_IDENTIFIER));
+    assert!(options.any(blocked));
+    assert!(!options.any(|o| o == &DhcpOption::VendorClassIdentifier(b"other".to_vec())));
+}
+
+#[test]
+fn test_classful_from_classless_host_route() {
+    let option = DhcpOption::ClasslessStaticRoute(vec![ClasslessStaticRoute {
+        destination: Ipv4Addr::new(10, 0, 0, 1),
+        prefix_length: 32,
+        router: Ipv4Addr::new(192, 168, 1, 1),
+    }]);
+
+    assert_eq!(option.classful_from_classless().unwrap(), DhcpOption::StaticRoute(vec![StaticRoute {
+        destination: Ipv4Addr::new(10, 0, 0, 1),
+        router: Ipv4Addr::new(192, 168, 1, 1),
+    }]));
+}
+
+#[test]
+fn test_classful_from_classless_network_route() {
+    let option = DhcpOption::ClasslessStaticRoute(vec![ClasslessStaticRoute {
+        destination: Ipv4Addr::new(10, 0, 0, 0),
+        prefix_length: 24,
+        router: Ipv4Addr::new(192, 168, 1, 1),
+    }]);
+
+    assert!(option.classful_from_classless().is_err());
+}
+
+#[test]
+fn test_conflicting_routes() {
+    let route = |destination: [u8; 4], router: [u8; 4]| StaticRoute {
+        destination: destination.into(),
+        router: router.into(),
+    };
+
+    let option = DhcpOption::StaticRoute(vec![
+        route([10, 0, 0, 1], [192, 168, 1, 1]),
+        route([10, 0, 0, 1], [192, 168, 1, 2]),
+    ]);
+    assert!(option.has_conflicting_routes());
+
+    let option = DhcpOption::StaticRoute(vec![
+        route([10, 0, 0, 1], [192, 168, 1, 1]),
+        route([10, 0, 0, 2], [192, 168, 1, 1]),
+        route([10, 0, 0, 1], [192, 168, 1, 1]),
+    ]);
+    assert!(!option.has_conflicting_routes());
+}
+
+#[test]
+fn test_ipxe_features() {
+    let option = DhcpOption::Unknown(IPXE_ENCAPSULATED, vec![19, 1, 1, 235, 3, 1, 0, 23]);
+    assert_eq!(option.ipxe_features().unwrap(), vec![(19, vec![1]), (235, vec![1, 0, 23])]);
+
+    let truncated = DhcpOption::Unknown(IPXE_ENCAPSULATED, vec![19, 2, 1]);
+    assert!(truncated.ipxe_features().is_err());
+}
+
+#[test]
+fn test_from_bytes_lenient_truncated() {
+    let bytes = [MESSAGE_TYPE, 1, 1, HOST_NAME, 5, b'h', b'o'];
+
+    assert!(DhcpOptions::from_bytes_lenient(&bytes[..3]).unwrap().1.is_empty());
+
+    let (options, notes) = DhcpOptions::from_bytes_lenient(&bytes).unwrap();
+    assert_eq!(options.message_type(), Some(&DhcpOption::MessageType(MessageType::Discover)));
+    assert!(options.option(HOST_NAME).is_none());
+    assert_eq!(notes, vec![ParseNote::TruncatedLastOption(HOST_NAME)]);
+}
+
+#[test]
+fn test_merge_preserving_unknown() {
+    let first = || DhcpOptions::from(vec![
+        DhcpOption::Unknown(224, vec![1, 2]),
+        DhcpOption::HostName(AsciiString::from_ascii("first").unwrap()),
+    ]);
+    let second = || DhcpOptions::from(vec![
+        DhcpOption::Unknown(224, vec![3]),
+        DhcpOption::HostName(AsciiString::from_ascii("second").unwrap()),
+    ]);
+
+    let mut options = first();
+    options.merge_preserving_unknown(second(), UnknownMergePolicy::Concatenate);
+    assert_eq!(options.option(224), Some(&DhcpOption::Unknown(224, vec![1, 2, 3])));
+    assert_eq!(options.option(HOST_NAME), Some(&DhcpOption::HostName(AsciiString::from_ascii("second").unwrap())));
+
+    let mut options = first();
+    options.merge_preserving_unknown(second(), UnknownMergePolicy::KeepExisting);
+    assert_eq!(options.option(224), Some(&DhcpOption::Unknown(224, vec![1, 2])));
+}
+
+#[test]
+fn test_classless_static_route_round_trip() {
+    let bytes = [CLASSLESS_STATIC_ROUTE, 8, 24, 10, 0, 0, 192, 168, 1, 1, END];
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+
+    let option = options.option(CLASSLESS_STATIC_ROUTE).unwrap();
+    assert_eq!(option, &DhcpOption::ClasslessStaticRoute(vec![ClasslessStaticRoute {
+        destination: Ipv4Addr::new(10, 0, 0, 0),
+        prefix_length: 24,
+        router: Ipv4Addr::new(192, 168, 1, 1),
+    }]));
+    assert_eq!(option.to_bytes(), &bytes[..10]);
+}
+
+#[test]
+fn test_user_class() {
+    let bytes = [USER_CLASS, 9, 4, b'i', b'P', b'X', b'E', 3, b'w', b'i', b'n', END];
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+
+    let option = options.option(USER_CLASS).unwrap();
+    assert_eq!(option, &DhcpOption::UserClass(vec![b"iPXE".to_vec(), b"win".to_vec()]));
+    assert_eq!(option.to_bytes(), &bytes[..11]);
+
+    let overrun = [USER_CLASS, 3, 4, b'i', b'P', END];
+    assert!(matches!(DhcpOptions::from_bytes(&overrun), Err(DhcpError::OptionParseError(USER_CLASS))));
+}
+
+#[test]
+fn test_to_bytes_tag_order() {
+    let options = DhcpOptions::from(vec![
+        DhcpOption::End,
+        DhcpOption::MessageType(MessageType::Discover),
+        DhcpOption::Unknown(224, vec![1]),
+        DhcpOption::Router(vec![Ipv4Addr::new(10, 0, 0, 1)]),
+        DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0)),
+    ]);
+
+    let (raw, _) = DhcpOptions::raw_from_bytes(&options.to_bytes()).unwrap();
+    let tags: Vec<u8> = raw.iter().map(|(tag, _)| *tag).collect();
+    assert_eq!(tags, vec![SUBNET_MASK, ROUTER, MESSAGE_TYPE, 224]);
+    assert_eq!(options.to_bytes().last(), Some(&END));
+}
+
+#[test]
+fn test_sip_servers() {
+    let bytes = [SIP_SERVERS, 9, 1, 10, 0, 0, 1, 10, 0, 0, 2, END];
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    let option = options.option(SIP_SERVERS).unwrap();
+    assert_eq!(option, &DhcpOption::SipServers(SipServers::Addresses(vec![
+        Ipv4Addr::new(10, 0, 0, 1),
+        Ipv4Addr::new(10, 0, 0, 2),
+    ])));
+    assert_eq!(option.to_bytes(), &bytes[..11]);
+
+    let bytes = [SIP_SERVERS, 12, 0, 3, b's', b'i', b'p', 3, b'c', b'o', b'm', 0, 0xc0, 20, END];
+    assert!(matches!(DhcpOptions::from_bytes(&bytes), Err(DhcpError::OptionParseError(SIP_SERVERS))));
+
+    let bytes = [SIP_SERVERS, 10, 0, 3, b's', b'i', b'p', 3, b'c', b'o', b'm', 0, END];
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    let option = options.option(SIP_SERVERS).unwrap();
+    assert_eq!(option, &DhcpOption::SipServers(SipServers::DomainNames(vec![
+        AsciiString::from_ascii("sip.com").unwrap(),
+    ])));
+    assert_eq!(option.to_bytes(), &bytes[..12]);
+
+    let bytes = [SIP_SERVERS, 5, 2, 10, 0, 0, 1, END];
+    assert!(matches!(DhcpOptions::from_bytes(&bytes), Err(DhcpError::OptionParseError(SIP_SERVERS))));
+}
+
+#[test]
+fn test_captive_portal() {
+    let uri = b"https://portal.example/api";
+    let mut bytes = vec![CAPTIVE_PORTAL, uri.len() as u8];
+    bytes.extend_from_slice(uri);
+    bytes.push(END);
+
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    let option = options.option(CAPTIVE_PORTAL).unwrap();
+    assert_eq!(option, &DhcpOption::CaptivePortal(AsciiString::from_ascii(&uri[..]).unwrap()));
+    assert_eq!(option.try_to_bytes().unwrap(), &bytes[..bytes.len() - 1]);
+
+    let too_long = DhcpOption::CaptivePortal(AsciiString::from_ascii(vec![b'a'; 256]).unwrap());
+    assert!(matches!(too_long.try_to_bytes(), Err(DhcpError::OptionInvalidValueError(CAPTIVE_PORTAL))));
+}
+
+#[test]
+fn test_from_bytes_with_len_trailing_pad() {
+    let bytes = [PAD, MESSAGE_TYPE, 1, 1, END, PAD, PAD, PAD];
+    let (options, length) = DhcpOptions::from_bytes_with_len(&bytes).unwrap();
+
+    assert_eq!(length, 5);
+    assert_eq!(options.message_type(), Some(&DhcpOption::MessageType(MessageType::Discover)));
+    assert_eq!(options.option(END), Some(&DhcpOption::End));
+}
+
+#[test]
+fn test_client_architecture() {
+    let bytes = [CLIENT_ARCHITECTURE, 4, 0, 0, 0, 7, END];
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    let option = options.option(CLIENT_ARCHITECTURE).unwrap();
+    assert_eq!(option, &DhcpOption::ClientArchitecture(vec![
+        crate::convert::CLIENT_ARCHITECTURE_INTEL_X86PC,
+        crate::convert::CLIENT_ARCHITECTURE_EFI_BC,
+    ]));
+    assert_eq!(option.to_bytes(), &bytes[..6]);
+
+    let odd = [CLIENT_ARCHITECTURE, 3, 0, 0, 7, END];
+    assert!(matches!(DhcpOptions::from_bytes(&odd), Err(DhcpError::OptionParseError(CLIENT_ARCHITECTURE))));
+}
+
+#[test]
+fn test_client_machine_identifier() {
+    let mut bytes = vec![CLIENT_MACHINE_IDENTIFIER, 17, 0];
+    bytes.extend(1..=16);
+    bytes.push(END);
+
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    let option = options.option(CLIENT_MACHINE_IDENTIFIER).unwrap();
+    assert_eq!(option, &DhcpOption::ClientMachineIdentifier(ClientMachineIdentifier {
+        typ: 0,
+        uuid: [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+    }));
+    assert_eq!(option.to_bytes(), &bytes[..19]);
+
+    let short = [CLIENT_MACHINE_IDENTIFIER, 3, 0, 1, 2, END];
+    assert!(matches!(DhcpOptions::from_bytes(&short), Err(DhcpError::OptionParseError(CLIENT_MACHINE_IDENTIFIER))));
+}
+
+#[test]
+fn test_lease_time_from_duration() {
+    let day = Duration::from_secs(24 * 60 * 60);
+    assert_eq!(DhcpOption::lease_time_from_duration(day).unwrap(), DhcpOption::IpAddressLeaseTime(86400));
+    assert_eq!(DhcpOption::renewal_time_from_duration(day / 2).unwrap(), DhcpOption::RenewalTimeValue(43200));
+
+    let long = Duration::from_secs(u64::from(u32::MAX) + 1);
+    assert_eq!(DhcpOption::rebinding_time_from_duration(long).unwrap(), DhcpOption::RebindingTimeValue(u32::MAX));
+
+    assert!(matches!(DhcpOption::lease_time_from_duration(Duration::from_millis(500)),
+                     Err(DhcpError::OptionInvalidValueError(IP_ADDRESS_LEASE_TIME))));
+}
+
+#[test]
+fn test_unknown_options() {
+    let bytes = [MESSAGE_TYPE, 1, 1, 224, 2, 1, 2, 250, 1, 3, END];
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+
+    assert_eq!(options.unknown_options(), vec![(224, &[1, 2][..]), (250, &[3][..])]);
+}
+
+#[test]
+fn test_rapid_commit() {
+    let bytes = [MESSAGE_TYPE, 1, 1, RAPID_COMMIT, 0, END];
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+
+    assert!(options.has_rapid_commit());
+    assert_eq!(options.option(RAPID_COMMIT).unwrap().to_bytes(), vec![RAPID_COMMIT, 0]);
+    assert!(!DhcpOptions::from_bytes(&[END]).unwrap().has_rapid_commit());
+}
+
+#[test]
+fn test_set_broadcast_from_mask() {
+    let mut options = DhcpOptions::from(vec![DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0))]);
+    options.set_broadcast_from_mask(Ipv4Addr::new(192, 168, 1, 0)).unwrap();
+    assert_eq!(options.option(BROADCAST_ADDRESS), Some(&DhcpOption::BroadcastAddress(Ipv4Addr::new(192, 168, 1, 255))));
+
+    let mut options = DhcpOptions::from(vec![DhcpOption::SubnetMask(Ipv4Addr::new(255, 0, 255, 0))]);
+    assert!(matches!(options.set_broadcast_from_mask(Ipv4Addr::new(192, 168, 1, 0)),
+                     Err(DhcpError::OptionInvalidValueError(SUBNET_MASK))));
+
+    let mut options = DhcpOptions::new();
+    assert!(matches!(options.set_broadcast_from_mask(Ipv4Addr::new(192, 168, 1, 0)),
+                     Err(DhcpError::OptionNotExist(SUBNET_MASK))));
+}
+
+#[test]
+fn test_parameter_request_list() {
+    let options = DhcpOptions::from(vec![
+        DhcpOption::ParameterRequestList(vec![SUBNET_MASK, ROUTER, DOMAIN_NAME_SERVER]),
+        DhcpOption::ClientIdentifier(ClientIdentifier::new(1, vec![0, 1, 2, 3, 4, 5])),
+    ]);
+
+    assert_eq!(options.parameter_request_list(), Some(&[SUBNET_MASK, ROUTER, DOMAIN_NAME_SERVER][..]));
+}
+
+#[test]
+fn test_from_bytes_truncated() {
+    let bytes = [MESSAGE_TYPE, 1, 1, HOST_NAME, 4, b'h', b'o', b's', b't', ROUTER, 4, 10, 0, 0, 1, END];
+    assert!(DhcpOptions::from_bytes(&bytes).is_ok());
+
+    assert!(matches!(DhcpOptions::from_bytes(&[]), Err(DhcpError::UnexpectedEndOfOptions)));
+    assert!(matches!(DhcpOptions::from_bytes(&bytes[..3]), Err(DhcpError::UnexpectedEndOfOptions)));
+    assert!(matches!(DhcpOptions::from_bytes(&bytes[..4]), Err(DhcpError::OptionParseError(HOST_NAME))));
+    assert!(matches!(DhcpOptions::from_bytes(&bytes[..7]), Err(DhcpError::OptionParseError(HOST_NAME))));
+
+    for length in 0..bytes.len() {
+        assert!(DhcpOptions::from_bytes(&bytes[..length]).is_err());
+    }
+}
+
+#[test]
+fn test_from_bytes_short_fixed_width() {
+    let fixed_width = [
+        (SUBNET_MASK, 4), (TIME_OFFSET, 4), (BOOT_FILE_SIZE, 2), (SWAP_SERVER, 4), (IP_FORWARDING, 1),
+        (NON_LOCAL_SOURCE_ROUTING, 1), (MAXIMUM_DATAGRAM_REASSEMBLY_SIZE, 2), (DEFAULT_IP_TTL, 1),
+        (PATH_MTU_AGING_TIMEOUT, 4), (INTERFACE_MTU, 2), (ALL_SUBNETS_LOCAL, 1), (BROADCAST_ADDRESS, 4),
+        (PERFORM_MASK_DISCOVERY, 1), (MASK_SUPPLIER, 1), (PERFORM_ROUTER_DISCOVERY, 1),
+        (ROUTER_SOLICITATION_ADDRESS, 4), (TRAILER_ENCAPSULATION, 1), (ARP_CACHE_TIMEOUT, 4),
+        (ETHERNET_ENCAPSULATION, 1), (TCP_DEFAULT_TTL, 1), (TCP_KEEPALIVE_INTERVAL, 4), (TCP_KEEPALIVE_GARGABE, 1),
+        (NETBIOS_OVER_TCP_IP_NODE_TYPE, 1), (REQUESTED_IP_ADDRESS, 4), (IP_ADDRESS_LEASE_TIME, 4),
+        (OPTION_OVERLOAD, 1), (MESSAGE_TYPE, 1), (SERVER_IDENTIFIER, 4), (MAXIMUM_DHCP_MESSAGE_SIZE, 2),
+        (RENEWAL_TIME_VALUE, 4), (REBINDING_TIME_VALUE, 4), (CLIENT_IDENTIFIER, 1), (CLIENT_ARCHITECTURE, 2),
+        (CLIENT_MACHINE_IDENTIFIER, 17), (AUTHENTICATION, 11),
+    ];
+
+    for (tag, width) in fixed_width {
+        for length in 0..width {
+            let mut bytes = vec![tag, length as u8];
+            bytes.resize(2 + length, 255);
+            bytes.push(END);
+            assert!(matches!(DhcpOptions::from_bytes(&bytes), Err(DhcpError::OptionParseError(t)) if t == tag),
+                    "tag {} with {} bytes", tag, length);
+        }
+    }
+}
+
+#[test]
+fn test_from_bytes_truncated_fixtures() {
+    let packet: Vec<u8> = crate::fixtures::full_packet().into();
+    let client_request: &[u8] = include_bytes!("../client_request.bin");
+
+    for options in [&packet[240..], &client_request[240..]] {
+        let end = DhcpOptions::from_bytes_with_len(options).unwrap().1;
+        for length in 0..end {
+            assert!(DhcpOptions::from_bytes(&options[..length]).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_split_vendor_specific() {
+    let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+    let option = DhcpOption::VendorSpecific(data.clone());
+
+    let mut bytes = option.to_bytes();
+    assert_eq!(bytes.len(), 300 + 4);
+    assert_eq!(&bytes[..2], &[VENDOR_SPECIFIC, 255]);
+    assert_eq!(&bytes[257..259], &[VENDOR_SPECIFIC, 45]);
+    assert_eq!(option.encoded_len(), bytes.len());
+    assert_eq!(option.try_to_bytes().unwrap(), bytes);
+
+    bytes.push(END);
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    assert_eq!(options.option(VENDOR_SPECIFIC), Some(&DhcpOption::VendorSpecific(data)));
+}
+
+#[test]
+fn test_split_lengths() {
+    for (length, instances) in [(0, 1), (255, 1), (256, 2), (510, 2), (511, 3)] {
+        let data: Vec<u8> = (0..length).map(|i| i as u8).collect();
+        let option = DhcpOption::Unknown(224, data.clone());
+
+        let mut bytes = option.to_bytes();
+        assert_eq!(bytes.len(), length + 2 * instances);
+        assert_eq!(option.encoded_len(), bytes.len());
+        assert_eq!(option.try_to_bytes().is_ok(), instances == 1);
+
+        bytes.push(END);
+        let options = DhcpOptions::from_bytes(&bytes).unwrap();
+        assert_eq!(options.option(224), Some(&DhcpOption::Unknown(224, data)));
+    }
+}
+
+#[test]
+fn test_to_raw_split_vendor_specific() {
+    let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+    let options = DhcpOptions::from(vec![DhcpOption::VendorSpecific(data.clone())]);
+
+    let raw = options.to_raw();
+    assert_eq!(raw.iter().map(|(tag, data)| (*tag, data.len())).collect::<Vec<_>>(),
+               vec![(VENDOR_SPECIFIC, 255), (VENDOR_SPECIFIC, 45)]);
+    assert_eq!(raw.iter().flat_map(|(_, data)| data.clone()).collect::<Vec<u8>>(), data);
+
+    let mut bytes = options.to_bytes();
+    bytes.push(END);
+    assert_eq!(DhcpOptions::from_bytes_with_raw(&bytes, &ParseOptions::default()).unwrap().1, raw);
+}
+
+#[test]
+fn test_split_domain_search() {
+    // 30 distinct names of 10 bytes each
+    let names: Vec<AsciiString> = (0..30)
+        .map(|i| AsciiString::from_ascii(format!("name{:02}.ex", i)).unwrap())
+        .collect();
+    let option = DhcpOption::DomainSearch(names.clone());
+
+    let mut bytes = option.to_bytes();
+    assert_eq!(&bytes[..2], &[DOMAIN_SEARCH, 255]);
+    assert_eq!(bytes[257], DOMAIN_SEARCH);
+    assert_eq!(option.encoded_len(), bytes.len());
+
+    bytes.push(END);
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    assert_eq!(options.option(DOMAIN_SEARCH), Some(&DhcpOption::DomainSearch(names)));
+}
+
+#[test]
+fn test_invalid_domain_names() {
+    let names = vec![AsciiString::from_ascii("a..b").unwrap()];
+    for option in [DhcpOption::DomainSearch(names.clone()), DhcpOption::SipServers(SipServers::DomainNames(names))] {
+        assert!(matches!(option.try_to_bytes(), Err(DhcpError::OptionInvalidValueError(tag)) if tag == option.tag()));
+    }
+
+    let label = AsciiString::from_ascii("a".repeat(64)).unwrap();
+    let option = DhcpOption::DomainSearch(vec![label]);
+    assert!(matches!(option.try_to_bytes(), Err(DhcpError::OptionInvalidValueError(DOMAIN_SEARCH))));
+    assert_eq!(option.to_bytes(), vec![DOMAIN_SEARCH, 0]);
+}
+
+#[test]
+fn test_split_sip_servers() {
+    let names: Vec<AsciiString> = (0..30)
+        .map(|i| AsciiString::from_ascii(format!("sip{:02}.example{:02}", i, i)).unwrap())
+        .collect();
+    let option = DhcpOption::SipServers(SipServers::DomainNames(names.clone()));
+
+    let mut bytes = option.to_bytes();
+    assert_eq!(&bytes[..3], &[SIP_SERVERS, 255, 0]);
+    assert_eq!(option.encoded_len(), bytes.len());
+    assert_eq!(bytes[257], SIP_SERVERS);
+    assert_eq!(option.try_to_bytes().unwrap(), bytes);
+
+    bytes.push(END);
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    assert_eq!(options.option(SIP_SERVERS), Some(&DhcpOption::SipServers(SipServers::DomainNames(names))));
+}
+
+#[test]
+fn test_perform_mask_and_router_discovery_distinct() {
+    let bytes = [PERFORM_MASK_DISCOVERY, 1, 1, PERFORM_ROUTER_DISCOVERY, 1, 0, END];
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+
+    assert_eq!(options.option(PERFORM_MASK_DISCOVERY), Some(&DhcpOption::PerformMaskDiscovery(true)));
+    assert_eq!(options.option(PERFORM_ROUTER_DISCOVERY), Some(&DhcpOption::PerformRouterDiscovery(false)));
+    assert_eq!(options.to_bytes(), bytes);
+}
+
+#[test]
+fn test_vendor_specific_suboptions() {
+    let sub_options = vec![(6, vec![8]), (10, vec![0, b'P', b'X', b'E'])];
+    let option = DhcpOption::vendor_specific_from_suboptions(sub_options.clone()).unwrap();
+    assert_eq!(option, DhcpOption::VendorSpecific(vec![6, 1, 8, 10, 4, 0, b'P', b'X', b'E']));
+    assert_eq!(option.vendor_specific_suboptions().unwrap(), sub_options);
+
+    let with_end = DhcpOption::VendorSpecific(vec![6, 1, 8, END, 10, 1]);
+    assert_eq!(with_end.vendor_specific_suboptions().unwrap(), vec![(6, vec![8])]);
+
+    let truncated = DhcpOption::VendorSpecific(vec![6, 3, 8]);
+    assert!(matches!(truncated.vendor_specific_suboptions(), Err(DhcpError::OptionParseError(VENDOR_SPECIFIC))));
+
+    assert!(DhcpOption::vendor_specific_from_suboptions(vec![(1, vec![0; 256])]).is_err());
+}
+
+#[test]
+fn test_client_identifier_accessors() {
+    let mac = MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let identifier = ClientIdentifier::new(1, mac.as_bytes().to_vec());
+
+    assert_eq!(identifier.typ(), 1);
+    assert_eq!(identifier.data(), &[0, 1, 2, 3, 4, 5]);
+    assert_eq!(identifier.as_mac(), Some(MacAddr::V6(mac)));
+
+    assert_eq!(ClientIdentifier::new(0, mac.as_bytes().to_vec()).as_mac(), None);
+    assert_eq!(ClientIdentifier::new(1, vec![0, 1, 2]).as_mac(), None);
+}
+
+#[test]
+fn test_contains_len_is_empty() {
+    let mut options = DhcpOptions::new();
+    assert!(options.is_empty());
+    assert_eq!(options.len(), 0);
+
+    options.upsert(DhcpOption::MessageType(MessageType::Discover));
+    options.upsert(DhcpOption::End);
+    options.upsert(DhcpOption::End);
+    assert!(options.contains(MESSAGE_TYPE));
+    assert!(!options.contains(HOST_NAME));
+    assert_eq!(options.len(), 2);
+    assert!(!options.is_empty());
+
+    options.remove(MESSAGE_TYPE);
+    assert!(!options.contains(MESSAGE_TYPE));
+    assert_eq!(options.len(), 1);
+}
+
+#[test]
+fn test_default() {
+    assert!(DhcpOptions::default().options().is_empty());
+}
+
+#[test]
+fn test_index() {
+    let mut options = DhcpOptions::from(vec![DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0))]);
+    assert_eq!(options[SUBNET_MASK], Some(DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0))));
+    assert_eq!(options[ROUTER], None);
+
+    options[SUBNET_MASK] = None;
+    assert!(!options.contains(SUBNET_MASK));
+    assert!(options.clone().options.is_empty());
+}
+
+#[test]
+fn test_from_iterator() {
+    let configured = vec![
+        DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0)),
+        DhcpOption::Router(vec![Ipv4Addr::new(192, 168, 1, 1)]),
+        DhcpOption::HostName(AsciiString::from_ascii("host").unwrap()),
+    ];
+    let options: DhcpOptions = configured.into_iter()
+        .filter(|o| !matches!(o, DhcpOption::HostName(_)))
+        .collect();
+    assert!(options.contains(SUBNET_MASK));
+    assert!(options.contains(ROUTER));
+    assert!(!options.contains(HOST_NAME));
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0)).to_string(), "SubnetMask(255.255.255.0)");
+    assert_eq!(DhcpOption::Router(vec![Ipv4Addr::new(192, 168, 1, 1), Ipv4Addr::new(192, 168, 1, 2)]).to_string(),
+               "Router([192.168.1.1, 192.168.1.2])");
+    assert_eq!(DhcpOption::MessageType(MessageType::Discover).to_string(), "MessageType(Discover)");
+    assert_eq!(DhcpOption::Unknown(224, vec![0x0a, 0xff]).to_string(), "Unknown(224, 0aff)");
+    assert_eq!(DhcpOption::End.to_string(), "End");
+}
+
+#[test]
+fn test_slot_iteration() {
+    let slots = DhcpOptions::new_with_options(vec![DhcpOption::RapidCommit]);
+    assert_eq!(slots.len(), 256);
+    assert_eq!(slots[RAPID_COMMIT as usize], Some(DhcpOption::RapidCommit));
+
+    let mut options = DhcpOptions::from(vec![DhcpOption::RapidCommit, DhcpOption::End]);
+    assert_eq!(options.iter().count(), 256);
+    assert_eq!(options.iter().nth(RAPID_COMMIT as usize), Some(&Some(DhcpOption::RapidCommit)));
+    assert_eq!(options.iter_mut().count(), 2);
+    assert_eq!(options.options.len(), 2);
+
+    if let Some(slot) = options.iter_mut().next() {
+        *slot = None;
+    }
+    assert!(!options.contains(RAPID_COMMIT));
+    assert_eq!(options.clone().options.len(), 1);
+
+    let slots: Vec<Option<DhcpOption>> = options.into_iter().collect();
+    assert_eq!(slots.len(), 256);
+    assert_eq!(slots[END as usize], Some(DhcpOption::End));
+}
+
+#[test]
+fn test_map_store_matches_fixed_vec_bytes() {
+    let packet = crate::fixtures::full_packet();
+    let options: Vec<DhcpOption> = packet.options().options().into_iter().rev().cloned().collect();
+
+    // previous store: 256 slots indexed by tag
+    let mut fixed: Vec<Option<DhcpOption>> = vec![None; 256];
+    for option in options.iter().cloned() {
+        let tag = option.tag() as usize;
+        fixed[tag] = Some(option);
+    }
+    let fixed_bytes: Vec<u8> = fixed.into_iter().flatten().flat_map(|o| o.to_bytes()).collect();
+
+    let bytes = DhcpOptions::from(options).to_bytes();
+    assert_eq!(bytes, fixed_bytes);
+    assert_eq!(bytes.last(), Some(&END));
+    assert_eq!(DhcpOptions::from_bytes(&bytes).unwrap().to_bytes(), fixed_bytes);
+}
+
+#[test]
+fn test_options_write_to() {
+    let options = crate::fixtures::full_packet().options().clone();
+    let mut buf = vec![1, 2];
+    options.write_to(&mut buf);
+    assert_eq!(&buf[..2], &[1, 2]);
+    assert_eq!(&buf[2..], options.to_bytes().as_slice());
+}
+
+#[test]
+fn test_message_type_force_renew() {
+    let option = DhcpOption::MessageType(MessageType::ForceRenew);
+    let bytes = option.to_bytes();
+    assert_eq!(bytes, vec![MESSAGE_TYPE, 1, 9]);
+    assert_eq!(DhcpOption::from_bytes(MESSAGE_TYPE, 1, &bytes[2..]).unwrap(), option);
+}
+
+#[test]
+fn test_message_type_lease_query() {
+    for (message_type, value) in [(MessageType::LeaseQuery, 10), (MessageType::LeaseUnassigned, 11),
+                                  (MessageType::LeaseUnknown, 12), (MessageType::LeaseActive, 13)] {
+        let option = DhcpOption::MessageType(message_type);
+        let bytes = option.to_bytes();
+        assert_eq!(bytes, vec![MESSAGE_TYPE, 1, value]);
+        assert_eq!(DhcpOption::from_bytes(MESSAGE_TYPE, 1, &bytes[2..]).unwrap(), option);
+    }
+}
+
+#[test]
+fn test_subnet_mask_contiguous() {
+    let option = DhcpOption::from_bytes(SUBNET_MASK, 4, &[255, 255, 255, 0]).unwrap();
+    assert_eq!(option, DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0)));
+    assert_eq!(option.prefix_length(), Some(24));
+    assert_eq!(DhcpOption::SubnetMask(Ipv4Addr::new(0, 0, 0, 0)).prefix_length(), Some(0));
+    assert_eq!(DhcpOption::Router(vec![]).prefix_length(), None);
+
+    assert!(matches!(DhcpOption::from_bytes(SUBNET_MASK, 4, &[255, 0, 255, 0]),
+                     Err(DhcpError::OptionInvalidValueError(SUBNET_MASK))));
+    assert_eq!(DhcpOption::SubnetMask(Ipv4Addr::new(255, 0, 255, 0)).prefix_length(), None);
+}
+
+#[test]
+fn test_message() {
+    let mut options = DhcpOptions::new();
+    assert_eq!(options.message(), None);
+
+    let message = AsciiString::from_ascii("address not available").unwrap();
+    options.upsert(DhcpOption::Message(message.clone()));
+    assert_eq!(options.message(), Some(&message));
+}
+
+#[test]
+fn test_server_identifier_and_requested_ip() {
+    let mut options = DhcpOptions::new();
+    assert_eq!(options.server_identifier(), None);
+    assert_eq!(options.requested_ip(), None);
+
+    options.upsert(DhcpOption::ServerIdentifier(Ipv4Addr::new(10, 0, 0, 1)));
+    options.upsert(DhcpOption::RequestedIpAddress(Ipv4Addr::new(10, 0, 0, 2)));
+    assert_eq!(options.server_identifier(), Some(Ipv4Addr::new(10, 0, 0, 1)));
+    assert_eq!(options.requested_ip(), Some(Ipv4Addr::new(10, 0, 0, 2)));
+}
+
+#[test]
+fn test_lease_times() {
+    let mut options = DhcpOptions::from(vec![
+        DhcpOption::IpAddressLeaseTime(7200),
+        DhcpOption::RenewalTimeValue(3600),
+        DhcpOption::RebindingTimeValue(u32::MAX),
+    ]);
+    assert_eq!(options.lease_time(), Some(Duration::from_secs(7200)));
+    assert_eq!(options.renewal_time(), Some(Duration::from_secs(3600)));
+    assert_eq!(options.rebinding_time(), Some(INFINITE_TIME));
+
+    assert!(options.rebinding_time() > Some(Duration::from_secs(u64::from(u32::MAX - 1))));
+    assert!(std::time::Instant::now().checked_add(INFINITE_TIME).is_some());
+
+    options.upsert(DhcpOption::IpAddressLeaseTime(u32::MAX));
+    assert_eq!(options.lease_time(), Some(INFINITE_TIME));
+    assert_eq!(DhcpOption::lease_time_from_duration(INFINITE_TIME).unwrap(), DhcpOption::IpAddressLeaseTime(u32::MAX));
+
+    options.remove(IP_ADDRESS_LEASE_TIME);
+    assert_eq!(options.lease_time(), None);
+}
+
+#[test]
+fn test_first_addresses() {
+    let options = DhcpOptions::from(vec![
+        DhcpOption::Router(vec![Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)]),
+        DhcpOption::DomainNameServer(vec![Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(8, 8, 8, 8)]),
+        DhcpOption::NetworkTimeProtocolServers(vec![Ipv4Addr::new(10, 0, 0, 3), Ipv4Addr::new(10, 0, 0, 4)]),
+    ]);
+    assert_eq!(options.first_router(), Some(Ipv4Addr::new(10, 0, 0, 1)));
+    assert_eq!(options.first_dns(), Some(Ipv4Addr::new(1, 1, 1, 1)));
+    assert_eq!(options.first_ntp(), Some(Ipv4Addr::new(10, 0, 0, 3)));
+
+    let options = DhcpOptions::from(vec![DhcpOption::Router(vec![])]);
+    assert_eq!(options.first_router(), None);
+    assert_eq!(options.first_dns(), None);
+}
+
+#[test]
+fn test_iter_present() {
+    let mut options = DhcpOptions::from(vec![
+        DhcpOption::MessageType(MessageType::Offer),
+        DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0)),
+        DhcpOption::End,
+        DhcpOption::Router(vec![Ipv4Addr::new(10, 0, 0, 1)]),
+    ]);
+    options[HOST_NAME] = None;
+
+    let tags: Vec<u8> = options.iter_present().map(|(tag, _)| tag).collect();
+    assert_eq!(tags, vec![SUBNET_MASK, ROUTER, MESSAGE_TYPE, END]);
+    assert!(options.iter_present().all(|(tag, option)| option.tag() == tag));
+}
+
+#[test]
+fn test_filtered_by_prl() {
+    let options = DhcpOptions::from(vec![
+        DhcpOption::SubnetMask(Ipv4Addr::new(255, 255, 255, 0)),
+        DhcpOption::Router(vec![Ipv4Addr::new(10, 0, 0, 1)]),
+        DhcpOption::DomainNameServer(vec![Ipv4Addr::new(1, 1, 1, 1)]),
+        DhcpOption::DomainName(AsciiString::from_ascii("example.com").unwrap()),
+        DhcpOption::NetworkTimeProtocolServers(vec![Ipv4Addr::new(10, 0, 0, 3)]),
+        DhcpOption::MessageType(MessageType::Offer),
+        DhcpOption::ServerIdentifier(Ipv4Addr::new(10, 0, 0, 1)),
+        DhcpOption::End,
+    ]);
+
+    let filtered = options.filtered_by_prl(&[1, 3, 6]);
+    let tags: Vec<u8> = filtered.iter_present().map(|(tag, _)| tag).collect();
+    assert_eq!(tags, vec![SUBNET_MASK, ROUTER, DOMAIN_NAME_SERVER, MESSAGE_TYPE, SERVER_IDENTIFIER, END]);
+}
+
+#[test]
+fn test_authentication_round_trip() {
+    // HMAC-MD5 delayed authentication (rfc 3118 5.)
+    let mut bytes = vec![AUTHENTICATION, 31, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 42, 0, 0, 0, 7];
+    bytes.extend_from_slice(&[0xab; 16]);
+    bytes.push(END);
+
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    let option = options.option(AUTHENTICATION).unwrap();
+    assert_eq!(option, &DhcpOption::Authentication(Authentication {
+        protocol: 1,
+        algorithm: 1,
+        rdm: 0,
+        replay_detection: 42,
+        auth_info: [[0, 0, 0, 7].as_ref(), &[0xab; 16]].concat(),
+    }));
+    assert_eq!(option.to_bytes(), bytes[..bytes.len() - 1].to_vec());
+
+    let short = [AUTHENTICATION, 10, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, END];
+    assert!(matches!(DhcpOptions::from_bytes(&short), Err(DhcpError::OptionParseError(AUTHENTICATION))));
+}
+
+#[test]
+fn test_authentication_long_auth_info() {
+    let option = DhcpOption::Authentication(Authentication {
+        protocol: 1,
+        algorithm: 1,
+        rdm: 0,
+        replay_detection: 42,
+        auth_info: vec![0xab; 250],
+    });
+    assert!(matches!(option.try_to_bytes(), Err(DhcpError::OptionInvalidValueError(AUTHENTICATION))));
+
+    let mut bytes = option.to_bytes();
+    assert_eq!(&bytes[..2], &[AUTHENTICATION, 255]);
+    assert_eq!(&bytes[257..259], &[AUTHENTICATION, 6]);
+
+    bytes.push(END);
+    let options = DhcpOptions::from_bytes(&bytes).unwrap();
+    assert_eq!(options.option(AUTHENTICATION), Some(&option));
+}
+
+#[test]
+fn test_round_trip() {
+    let tree_name = "CORP_TRÉE".as_bytes();
+    let cases = vec![
+        (vec![NETWORK_INFORMATION_SERVERS, 4, 10, 0, 0, 1], DhcpOption::NetworkInformationServers(vec![Ipv4Addr::new(10, 0, 0, 1)])),
+        (vec![MS_CLASSLESS_STATIC_ROUTE, 8, 24, 10, 0, 0, 192, 168, 1, 1], DhcpOption::MsClasslessStaticRoute(vec![ClasslessStaticRoute {
+            destination: Ipv4Addr::new(10, 0, 0, 0),
+            prefix_length: 24,
+            router: Ipv4Addr::new(192, 168, 1, 1),
+        }])),
+        (vec![TFTP_SERVER_ADDRESS, 8, 10, 0, 0, 2, 10, 0, 0, 3],
+         DhcpOption::TftpServerAddress(vec![Ipv4Addr::new(10, 0, 0, 2), Ipv4Addr::new(10, 0, 0, 3)])),
+        (vec![PXE_MAGIC, 4, 0xf1, 0x00, 0x74, 0x7e], DhcpOption::PxeMagic(vec![0xf1, 0x00, 0x74, 0x7e])),
+        ([&[PXE_CONFIG_FILE, 7], &b"pxe.cfg"[..]].concat(), DhcpOption::PxeConfigFile(AsciiString::from_ascii("pxe.cfg").unwrap())),
+        ([&[PXE_PATH_PREFIX, 6], &b"/boot/"[..]].concat(), DhcpOption::PxePathPrefix(AsciiString::from_ascii("/boot/").unwrap())),
+        (vec![PXE_REBOOT_TIME, 4, 0, 0, 1, 44], DhcpOption::PxeRebootTime(300)),
+        ([&[POSIX_TIMEZONE, 7], &b"PST8PDT"[..]].concat(), DhcpOption::PosixTimezone(AsciiString::from_ascii("PST8PDT").unwrap())),
+        ([&[TIMEZONE_DATABASE, 19], &b"America/Los_Angeles"[..]].concat(),
+         DhcpOption::TimezoneDatabase(AsciiString::from_ascii("America/Los_Angeles").unwrap())),
+        (vec![NDS_SERVERS, 8, 10, 0, 0, 5, 10, 0, 0, 6], DhcpOption::NdsServers(vec![Ipv4Addr::new(10, 0, 0, 5), Ipv4Addr::new(10, 0, 0, 6)])),
+        ([&[NDS_TREE_NAME, tree_name.len() as u8], tree_name].concat(), DhcpOption::NdsTreeName(tree_name.to_vec())),
+    ];
+
+    for (bytes, option) in cases {
+        let options = DhcpOptions::from_bytes(&[&bytes[..], &[END]].concat()).unwrap();
+        assert_eq!(options.option(option.tag()), Some(&option));
+        assert_eq!(option.to_bytes(), bytes);
+    }
+}
+
+#[test]
+fn test_below_minimum_length() {
+    let cases: Vec<&[u8]> = vec![
+        &[TFTP_SERVER_ADDRESS, 3, 10, 0, 0, END],
+        &[TIMEZONE_DATABASE, 0, END],
+        &[NDS_SERVERS, 2, 10, 0, END],
+    ];
+
+    for bytes in cases {
+        assert!(DhcpOptions::from_bytes(bytes).is_err());
+    }
+}
+
+#[cfg(feature = "with_serde")]
+#[test]
+fn test_options_by_tag_serde() {
+    let options: DhcpOptions = vec![
+        DhcpOption::MessageType(MessageType::Discover),
+        DhcpOption::Router(vec![Ipv4Addr::new(192, 168, 1, 1)]),
+        DhcpOption::End,
+    ].into_iter().collect();
+
+    let json = serde_json::to_string(&DhcpOptionsByTag::from(options.clone())).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["53"], serde_json::json!({"MessageType": "Discover"}));
+    assert!(value.get("3").is_some());
+
+    let parsed: DhcpOptionsByTag = serde_json::from_str(&json).unwrap();
+    assert_eq!(DhcpOptions::from(parsed), options);
+
+    let mismatch = r#"{"54":{"MessageType":"Discover"}}"#;
+    assert!(serde_json::from_str::<DhcpOptionsByTag>(mismatch).is_err());
 }
@@ -0,0 +1,158 @@
+use std::convert::TryFrom;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, ToSocketAddrs, UdpSocket};
+use crate::error::{DhcpError, DhcpResult};
+use crate::messaging::DhcpMessaging;
+
+pub use crate::dhcp::{SERVER_PORT, CLIENT_PORT};
+
+/// Receive buffer size, covers an ethernet MTU
+const RECV_BUFFER_SIZE: usize = 1500;
+
+/// UDP socket sending and receiving [`DhcpMessaging`]
+///
+/// Serializes and parses automatically. `SO_BROADCAST` is enabled on the socket before sending a
+/// packet with [`Flags::Broadcast`](crate::dhcp::Flags::Broadcast) set, it stays enabled afterwards.
+/// Sending a unicast flagged packet to a broadcast address fails unless broadcast was enabled
+/// before, e.g. with `socket().set_broadcast(true)`.
+#[derive(Debug)]
+pub struct DhcpSocket {
+    socket: UdpSocket,
+}
+
+impl DhcpSocket {
+    /// Binds to any address on the server port 67
+    pub fn bind_server() -> DhcpResult<Self> {
+        Self::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, SERVER_PORT))
+    }
+
+    /// Binds to any address on the client port 68
+    pub fn bind_client() -> DhcpResult<Self> {
+        Self::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, CLIENT_PORT))
+    }
+
+    pub fn bind<A: ToSocketAddrs>(address: A) -> DhcpResult<Self> {
+        let socket = UdpSocket::bind(address).map_err(socket_error)?;
+        Ok(Self { socket })
+    }
+
+    /// Underlying socket, e.g. to set timeouts
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Serializes and sends the message, returns the number of bytes sent
+    pub fn send_to(&self, message: DhcpMessaging, destination: SocketAddrV4) -> DhcpResult<usize> {
+        if message.packet().is_broadcast() {
+            self.socket.set_broadcast(true).map_err(socket_error)?;
+        }
+
+        let bytes: Vec<u8> = message.into();
+        self.socket.send_to(&bytes, destination).map_err(socket_error)
+    }
+
+    /// Receives and parses the next message
+    pub fn recv(&self) -> DhcpResult<DhcpMessaging> {
+        self.recv_from().map(|(message, _)| message)
+    }
+
+    /// Same as [`DhcpSocket::recv`] but also returns the sender address
+    pub fn recv_from(&self) -> DhcpResult<(DhcpMessaging, SocketAddr)> {
+        let mut buf = [0; RECV_BUFFER_SIZE];
+        let (length, source) = self.socket.recv_from(&mut buf).map_err(socket_error)?;
+        Ok((DhcpMessaging::try_from(&buf[..length])?, source))
+    }
+}
+
+/// Tokio variant of [`DhcpSocket`]
+///
+/// `SO_BROADCAST` is enabled before sending a packet with the broadcast flag set as well.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncDhcpSocket {
+    socket: tokio::net::UdpSocket,
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncDhcpSocket {
+    pub async fn bind<A: tokio::net::ToSocketAddrs>(address: A) -> DhcpResult<Self> {
+        let socket = tokio::net::UdpSocket::bind(address).await.map_err(socket_error)?;
+        Ok(Self { socket })
+    }
+
+    /// Underlying socket
+    pub fn socket(&self) -> &tokio::net::UdpSocket {
+        &self.socket
+    }
+
+    /// Serializes and sends the message, returns the number of bytes sent
+    pub async fn send(&self, message: DhcpMessaging, destination: SocketAddrV4) -> DhcpResult<usize> {
+        if message.packet().is_broadcast() {
+            self.socket.set_broadcast(true).map_err(socket_error)?;
+        }
+
+        let bytes: Vec<u8> = message.into();
+        self.socket.send_to(&bytes, destination).await.map_err(socket_error)
+    }
+
+    /// Receives and parses the next message
+    pub async fn recv(&self) -> DhcpResult<DhcpMessaging> {
+        self.recv_from().await.map(|(message, _)| message)
+    }
+
+    /// Same as [`AsyncDhcpSocket::recv`] but also returns the sender address
+    pub async fn recv_from(&self) -> DhcpResult<(DhcpMessaging, SocketAddr)> {
+        let mut buf = [0; RECV_BUFFER_SIZE];
+        let (length, source) = self.socket.recv_from(&mut buf).await.map_err(socket_error)?;
+        Ok((DhcpMessaging::try_from(&buf[..length])?, source))
+    }
+}
+
+fn socket_error(error: std::io::Error) -> DhcpError {
+    DhcpError::SocketError(error.kind())
+}
+
+#[test]
+fn test_loopback() {
+    let receiver = DhcpSocket::bind("127.0.0.1:0").unwrap();
+    let sender = DhcpSocket::bind("127.0.0.1:0").unwrap();
+    let destination = match receiver.socket().local_addr().unwrap() {
+        SocketAddr::V4(address) => address,
+        SocketAddr::V6(_) => unreachable!(),
+    };
+
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let discover = DhcpMessaging::discover_with_xid(0x1234, client_mac, None, None, None, None, None, None, None);
+    assert_eq!(*discover.packet().flags(), crate::dhcp::Flags::Broadcast);
+    sender.send_to(DhcpMessaging::Discover(discover), destination).unwrap();
+    assert!(sender.socket().broadcast().unwrap());
+
+    match receiver.recv_from().unwrap() {
+        (DhcpMessaging::Discover(discover), source) => {
+            assert_eq!(discover.packet().transaction_id(), 0x1234);
+            assert_eq!(source, sender.socket().local_addr().unwrap());
+        }
+        _ => panic!("expected discover"),
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_async_loopback() {
+    let receiver = AsyncDhcpSocket::bind("127.0.0.1:0").await.unwrap();
+    let sender = AsyncDhcpSocket::bind("127.0.0.1:0").await.unwrap();
+    let destination = match receiver.socket().local_addr().unwrap() {
+        SocketAddr::V4(address) => address,
+        SocketAddr::V6(_) => unreachable!(),
+    };
+
+    let client_mac = macaddr::MacAddr6::new(0, 1, 2, 3, 4, 5);
+    let discover = DhcpMessaging::discover_with_xid(0x1234, client_mac, None, None, None, None, None, None, None);
+    assert!(discover.packet().is_broadcast());
+    sender.send(DhcpMessaging::Discover(discover), destination).await.unwrap();
+    assert!(sender.socket().broadcast().unwrap());
+
+    match receiver.recv().await.unwrap() {
+        DhcpMessaging::Discover(discover) => assert_eq!(discover.packet().transaction_id(), 0x1234),
+        _ => panic!("expected discover"),
+    }
+}